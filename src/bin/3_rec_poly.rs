@@ -30,7 +30,10 @@ fn main() -> std::io::Result<()> {
                 iterations: 50_000,
                 ..Default::default()
             },
-            Options { print_freq: None },
+            Options {
+                print_freq: None,
+                ..Default::default()
+            },
         );
 
         if output.score.is_none() {
@@ -0,0 +1,97 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Recovers a recurrence from its sequence under a few annealing schedules, over a few seeds.
+fn main() {
+    let lang = Polynomials;
+    let ctxt = lang.context();
+
+    let target = parse_term("(\\f n -> plus(mult(f)(plus(n)(one)))(mult(n)(n)))").unwrap();
+
+    let mut nums = vec![1i32];
+    for n in 1..8 {
+        let prev = Term::val(*nums.last().unwrap());
+        let next = ctxt.evaluate(&term!([target] [prev] [Term::val(n)]));
+        nums.push(next.get::<i32>());
+    }
+
+    println!("Sequence: {:?}", nums);
+
+    // (The smallest term of this type is `plus`, which has no subterms to mutate)
+    let start = parse_term("(\\f n -> f)").unwrap();
+
+    let schedules = [
+        (
+            "linear",
+            Annealing {
+                schedule: Schedule::Linear {
+                    start: 4.,
+                    end: 0.1,
+                },
+                patience: None,
+            },
+        ),
+        (
+            "exponential, restarts",
+            Annealing {
+                schedule: Schedule::Exponential {
+                    start: 4.,
+                    decay: 0.9998,
+                },
+                patience: Some(5_000),
+            },
+        ),
+        (
+            "cosine",
+            Annealing {
+                schedule: Schedule::CosineRestarts {
+                    max: 4.,
+                    min: 0.1,
+                    period: 4_000,
+                },
+                patience: None,
+            },
+        ),
+    ];
+
+    for (name, annealing) in schedules {
+        for seed in 0..3 {
+            let examples = nums[1..]
+                .iter()
+                .cloned()
+                .enumerate()
+                .map(|(i, n)| ((i + 1) as i32, n));
+
+            let output = iterative(
+                lang,
+                nums[0],
+                examples,
+                Some(start.clone()),
+                ty!(N => N => N),
+                SynthesisParameters {
+                    bias: SizeBias::DistAbs { mean: 20, c: 0.5 },
+                    iterations: 20_000,
+                    annealing: Some(annealing.clone()),
+                    ..Default::default()
+                },
+                Options {
+                    print_freq: None,
+                    seed: Some(seed),
+                    ..Default::default()
+                },
+            );
+
+            println!(
+                "\n{}, seed {}: {} after {} iterations ({} correct, {} restarts)",
+                name,
+                seed,
+                output.term,
+                output.iterations,
+                output.num_correct,
+                output.chains[0].restarts
+            );
+        }
+    }
+}
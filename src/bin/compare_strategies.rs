@@ -0,0 +1,60 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Enumeration & metropolis on the same problems
+fn main() {
+    let lang = Polynomials;
+
+    let problems = [
+        Examples::map(ty!(N => N), (0..6).map(|x| (x, x * x + 1))),
+        Examples::map(ty!(N => N), (0..6).map(|x| (x, x * x * x + 2 * x))),
+        Examples::iterative(
+            ty!(N => N => N),
+            1,
+            (1..6).map(|i| (i, (1..=i).product::<i32>())),
+        ),
+    ];
+
+    for spec in &problems {
+        let enumerated = enumerative_synthesize(
+            lang,
+            spec,
+            Some(30),
+            SynthesisParameters::default(),
+            Options {
+                print_freq: None,
+                ..Default::default()
+            },
+        )
+        .expect("Every problem has programs up to size 30");
+
+        let sampled = synthesize(
+            &lang,
+            spec,
+            Stochastic {
+                start: None,
+                settings: SynthesisParameters::default(),
+                options: Options {
+                    print_freq: None,
+                    seed: Some(0),
+                    ..Default::default()
+                },
+            },
+        );
+
+        for (name, output) in [("Enumeration", enumerated), ("Metropolis", sampled)] {
+            println!(
+                "{:>11}: {} ({} of {} correct, {}) after {} iterations in {:.3}s",
+                name,
+                output.term,
+                output.num_correct,
+                spec.num_examples(),
+                output.stats.stop,
+                output.iterations,
+                output.time
+            );
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+use std::time::Instant;
+
+// Growth of the search space, counted without enumeration. `Opaque` doesn't prune
+// anything, so the counts are exact (& are checked against `search` while it's quick).
+fn main() {
+    let lang = Opaque;
+    let ty = ty!(N => N => N);
+
+    let mut counter = Counter::<BigUint>::new();
+
+    for n in 1..=64 {
+        let start = Instant::now();
+        let count = counter.count(&lang, vec![], &ty, n);
+        let time = start.elapsed().as_secs_f32();
+
+        if n <= 12 {
+            let enumerated = search(&lang, vec![], &ty, n).count();
+            assert_eq!(count, BigUint::from(enumerated));
+        }
+
+        println!("Size {:>2}: {:>40} programs ({}s)", n, count, time);
+    }
+}
@@ -24,7 +24,7 @@ fn main() {
 
     let programs = (1..)
         .inspect(|n| println!("Searching size: {}", n))
-        .flat_map(|n| search(&lang, vec![], &ty, n));
+        .flat_map(|n| par_search(&lang, vec![], &ty, n));
 
     let start = std::time::Instant::now();
 
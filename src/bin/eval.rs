@@ -0,0 +1,39 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Usage: eval "(\p n -> mult p (plus one one))" 4 3
+fn main() {
+    let lang = Polynomials;
+
+    let mut args = std::env::args().skip(1);
+
+    let Some(src) = args.next() else {
+        eprintln!("Usage: eval <term> [args...]");
+        std::process::exit(1);
+    };
+
+    let mut program = match parse_term(&src) {
+        Ok(term) => term,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("  {}", src);
+            eprintln!("  {:>width$}", "^", width = err.pos + 1);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Parsed (|t| = {}): {}", program.size(), program);
+
+    for arg in args {
+        let Ok(n) = arg.parse::<i32>() else {
+            eprintln!("Arguments must be integers, found `{}`", arg);
+            std::process::exit(1);
+        };
+
+        program = term!([program] [:n]);
+    }
+
+    println!("{}", lang.context().evaluate(&program));
+}
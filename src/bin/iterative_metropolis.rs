@@ -26,7 +26,10 @@ fn main() {
             iterations: 75_000,
             ..Default::default()
         },
-        Options { print_freq: None },
+        Options {
+            print_freq: None,
+            ..Default::default()
+        },
     );
 
     if output.score.is_none() {
@@ -0,0 +1,61 @@
+use kolmogorov::*;
+
+// Generic control flow, defined once for every type.
+#[derive(Clone, Debug)]
+pub struct Combinators;
+
+impl Language for Combinators {
+    type Semantics = OpaqueSemantics;
+
+    fn context(&self) -> Context {
+        let int = |t: &Term| t.get::<i32>();
+
+        let ite = builtin!(
+            forall a. Bool => a => a => a
+            |c| => if c.get::<bool>() {
+                term!(t e -> t)
+            } else {
+                term!(t e -> e)
+            }
+        );
+
+        // Applies `f` to `x`, `n` times
+        let iter = builtin!(
+            forall a. N => (a => a) => a => a
+            ctxt |n, f, x| => {
+                let mut x = x.clone();
+                for _ in 0..int(n).max(0) {
+                    x = ctxt.try_evaluate(&term!([f] [x])).ok()?;
+                }
+                x
+            }
+        );
+
+        let lte = builtin!(
+            N => N => Bool
+            |x, y| => Term::val(int(x) <= int(y))
+        );
+
+        let plus = builtin!(
+            N => N => N
+            |x, y| => Term::val(int(x).wrapping_add(int(y)))
+        );
+
+        let one = builtin!(
+            N
+            | | => Term::val(1i32)
+        );
+
+        let zero = builtin!(
+            N
+            | | => Term::val(0i32)
+        );
+
+        context! { ite, iter, lte, plus, one, zero }
+    }
+}
+
+#[allow(dead_code)]
+fn main() {
+    panic!("This file is not intended to be executed directly.")
+}
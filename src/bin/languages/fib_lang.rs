@@ -10,8 +10,8 @@ impl Language for FibLang {
         let int = |t: &Term| t.get::<i32>();
 
         let lte = builtin!(
-            N => N => N => N => N
-            |a, b| => if int(&a) <= int(&b) {
+            forall a. N => N => a => a => a
+            |a, b| => if int(a) <= int(b) {
                 term!(a b -> a)
             } else {
                 term!(a b -> b)
@@ -20,12 +20,12 @@ impl Language for FibLang {
 
         let plus = builtin!(
             N => N => N
-            |x, y| => Term::val(int(&x)+int(&y))
+            |x, y| => Term::val(int(x)+int(y))
         );
 
         let minus = builtin!(
             N => N => N
-            |x, y| => Term::val(int(&x)-int(&y))
+            |x, y| => Term::val(int(x)-int(y))
         );
 
         let one = builtin!(
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 
 use std::fmt::{write, Display};
-use std::rc::Rc;
+use std::sync::Arc;
 
 use kolmogorov::*;
 
@@ -31,7 +31,7 @@ type Conjunction = Vec<Literal>;
 pub struct Exists {
     var: Identifier,
     bound: Number,
-    body: Rc<LogicSems>,
+    body: Arc<LogicSems>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -40,7 +40,7 @@ pub enum LogicSems {
     And(Conjunction),                // A conjunction of Literals
     App(Identifier, Vec<LogicSems>), // Variables are not analyzed until they are contextualized
     Any(Exists),
-    Abs(Identifier, Rc<LogicSems>),
+    Abs(Identifier, Arc<LogicSems>),
 }
 
 impl Language for LogicLang {
@@ -83,7 +83,7 @@ impl Language for LogicLang {
             return Malformed;
         }
 
-        Canonical(Abs(ident, Rc::new(body.canon())))
+        Canonical(Abs(ident, Arc::new(body.canon())))
     }
 
     fn sapp(&self, fun: Analysis<Self>, arg: Analysis<Self>, _ty: &Type) -> Analysis<Self> {
@@ -162,7 +162,11 @@ impl LogicLang {
         let exists = builtin! {
             N => (N => Bool) => Bool
             ctxt |b, f| => {
-                Term::val((1..b.get::<u32>()).any(|n| ctxt.evaluate(&term!([f] [:n])).get::<bool>()))
+                let found = (1..b.get::<u32>())
+                    .map(|n| ctxt.try_evaluate(&term!([f] [:n])).ok()?.try_get::<bool>())
+                    .find(|sat| *sat != Some(false));
+
+                Term::val(found.unwrap_or(Some(false))?)
             }
         };
 
@@ -0,0 +1,100 @@
+use kolmogorov::*;
+
+use std::fmt::{self, Display};
+
+// Polymorphic lists, with the usual combinators.
+#[derive(Clone, Debug)]
+pub struct Lists;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListVal(pub Vec<Term>);
+
+impl Display for ListVal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, x) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", x)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl Language for Lists {
+    type Semantics = OpaqueSemantics;
+
+    fn context(&self) -> Context {
+        let int = |t: &Term| t.get::<i32>();
+        let list = |t: &Term| t.get::<ListVal>().0;
+
+        let nil = builtin!(
+            forall a. (List a)
+            | | => Term::val(ListVal(vec![]))
+        );
+
+        let cons = builtin!(
+            forall a. a => (List a) => (List a)
+            |x, xs| => {
+                let mut xs = list(xs);
+                xs.insert(0, x.clone());
+                Term::val(ListVal(xs))
+            }
+        );
+
+        let foldr = builtin!(
+            forall a b. (a => b => b) => b => (List a) => b
+            ctxt |f, z, xs| => {
+                let mut acc = z.clone();
+                for x in list(xs).iter().rev() {
+                    acc = ctxt.try_evaluate(&term!([f] [x] [acc])).ok()?;
+                }
+                acc
+            }
+        );
+
+        let map = builtin!(
+            forall a b. (a => b) => (List a) => (List b)
+            ctxt |f, xs| => {
+                let xs = list(xs)
+                    .iter()
+                    .map(|x| ctxt.try_evaluate(&term!([f] [x])).ok())
+                    .collect::<Option<_>>()?;
+                Term::val(ListVal(xs))
+            }
+        );
+
+        let length = builtin!(
+            forall a. (List a) => N
+            |xs| => Term::val(list(xs).len() as i32)
+        );
+
+        let plus = builtin!(
+            N => N => N
+            |x, y| => Term::val(int(x).wrapping_add(int(y)))
+        );
+
+        let mult = builtin!(
+            N => N => N
+            |x, y| => Term::val(int(x).wrapping_mul(int(y)))
+        );
+
+        let one = builtin!(
+            N
+            | | => Term::val(1i32)
+        );
+
+        let zero = builtin!(
+            N
+            | | => Term::val(0i32)
+        );
+
+        context! { nil, cons, foldr, map, length, plus, mult, one, zero }
+    }
+}
+
+#[allow(dead_code)]
+fn main() {
+    panic!("This file is not intended to be executed directly.")
+}
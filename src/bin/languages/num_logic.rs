@@ -1,6 +1,6 @@
 use std::fmt::{write, Display};
 use std::process::id;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use kolmogorov::*;
 
@@ -48,7 +48,7 @@ pub struct Reduction {
     reducer: Reducer,
     var: Identifier,
     bound: Var,
-    body: Rc<NumLogicSems>,
+    body: Arc<NumLogicSems>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -57,7 +57,7 @@ pub enum NumLogicSems {
     And(Conjunction),                   // A conjunction of Literals
     App(Identifier, Vec<NumLogicSems>), // Variables are not analyzed until they are contextualized
     Red(Reduction),
-    Abs(Identifier, Rc<NumLogicSems>),
+    Abs(Identifier, Arc<NumLogicSems>),
 }
 
 impl Language for NumLogic {
@@ -101,7 +101,7 @@ impl Language for NumLogic {
             return Malformed;
         }
 
-        Canonical(Abs(ident, Rc::new(body.canon())))
+        Canonical(Abs(ident, Arc::new(body.canon())))
     }
 
     fn sapp(&self, fun: Analysis<Self>, arg: Analysis<Self>, _ty: &Type) -> Analysis<Self> {
@@ -245,18 +245,22 @@ impl NumLogic {
 
         let pow = builtin! {
             Var => Var => Atom
-            |c, p| => Term::val(int(&c).checked_pow(int(&p)).unwrap_or(0))
+            |c, p| => Term::val(int(c).checked_pow(int(p)).unwrap_or(0))
         };
 
         let mul = builtin! {
             Atom => Atom => Atom
-            |l, r| => Term::val(int(&l).checked_mul(int(&r)).unwrap_or(0))
+            |l, r| => Term::val(int(l).checked_mul(int(r)).unwrap_or(0))
         };
 
         let exists = builtin! {
             Var => (Var => Bool) => Bool
             ctxt |b, f| => {
-                Term::val((1..=int(&b)).any(|n| bln(&ctxt.evaluate(&term!([f] [:n])))))
+                let found = (1..=int(b))
+                    .map(|n| apply::<bool>(ctxt, f, n))
+                    .find(|sat| *sat != Some(false));
+
+                Term::val(found.unwrap_or(Some(false))?)
             }
         };
 
@@ -268,14 +272,20 @@ impl NumLogic {
         let sigma = builtin! {
             Var => (Var => Num) => Num
             ctxt |b, f| => {
-                Term::val((1..=int(&b)).map(|n| int(&ctxt.evaluate(&term!([f] [:n])))).sum::<u32>())
+                let sum = (1..=int(b)).map(|n| apply::<u32>(ctxt, f, n)).sum::<Option<u32>>();
+
+                Term::val(sum?)
             }
         };
 
         let count = builtin! {
             Var => (Var => Bool) => Num
             ctxt |b, f| => {
-                Term::val((1..=int(&b)).filter(|&n| bln(&ctxt.evaluate(&term!([f] [:n])))).count() as u32)
+                let count = (1..=int(b))
+                    .map(|n| apply::<bool>(ctxt, f, n))
+                    .try_fold(0u32, |count, sat| Some(count + sat? as u32));
+
+                Term::val(count?)
             }
         };
 
@@ -291,31 +301,31 @@ impl NumLogic {
 
         let and = builtin! {
             Pred => Conj => Conj
-            |a, b| => Term::val(bln(&a) && bln(&b))
+            |a, b| => Term::val(bln(a) && bln(b))
         };
 
         let prime = builtin! {
             Atom => Pred
-            |n| => Term::val(is_prime(int(&n)))
+            |n| => Term::val(is_prime(int(n)))
         };
 
         let divisor = builtin! {
             Atom => Atom => Pred
             |p, q| => {
-                let p = int(&p);
-                let q = int(&q);
+                let p = int(p);
+                let q = int(q);
                 Term::val(p > 1 && q % p == 0)
             }
         };
 
         let eq = builtin! {
             Atom => Atom => Pred
-            |l, r| => Term::val(int(&l) == int(&r))
+            |l, r| => Term::val(int(l) == int(r))
         };
 
         let less = builtin! {
             Atom => Atom => Pred
-            |l, r| => Term::val(int(&l) < int(&r))
+            |l, r| => Term::val(int(l) < int(r))
         };
 
         vec![
@@ -348,6 +358,11 @@ impl NumLogicSems {
     }
 }
 
+// Evaluates `f n`. Fails if evaluation does (e.g., when out of fuel).
+fn apply<T: TermValue + Clone>(ctxt: &Context, f: &Term, n: u32) -> Option<T> {
+    ctxt.try_evaluate(&term!([f] [:n])).ok()?.try_get::<T>()
+}
+
 // Simple algorithm
 fn is_prime(n: u32) -> bool {
     if n < 2 {
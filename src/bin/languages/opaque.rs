@@ -17,12 +17,12 @@ impl Language for Opaque {
 
         let plus = builtin!(
             N => N => N
-            |x, y| => Term::val(int(&x)+int(&y))
+            |x, y| => Term::val(int(x)+int(y))
         );
 
         let mult = builtin!(
             N => N => N
-            |x, y| => Term::val(int(&x)*int(&y))
+            |x, y| => Term::val(int(x)*int(y))
         );
 
         let one = builtin!(
@@ -1,5 +1,5 @@
 use std::fmt::Display;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use kolmogorov::*;
 
@@ -0,0 +1,61 @@
+use kolmogorov::*;
+
+mod languages;
+mod utils;
+
+use languages::*;
+use utils::*;
+
+// Fits weights to the solutions found by `oeis_iterative`, & prints the resulting prior.
+// Usage: learn_prior [corpus]
+fn main() -> std::io::Result<()> {
+    let path = std::env::args().nth(1);
+    let path = path.as_deref().unwrap_or("data/oeis_iterative");
+
+    let ctxt = Polynomials.context();
+    let solutions = load_solutions(path)?;
+    let corpus = solutions.iter().map(|(_, term)| term);
+
+    println!("{} solutions", solutions.len());
+
+    let bigram = fit_bigram(&ctxt, corpus.clone(), 1.);
+    let prior = bigram.unigram().prior(0.5);
+
+    println!("\nHeads:");
+    for (head, log_prob) in bigram.unigram().weights() {
+        let cost = match head {
+            Head::Builtin(v) => format!(" (cost {})", prior.var_cost(v)),
+            _ => String::new(),
+        };
+        println!("{:>8}: p = {:.3}{}", head, log_prob.exp(), cost);
+    }
+
+    println!("\nChildren:");
+    for (parent, children) in bigram.top_children(3) {
+        let children = children
+            .iter()
+            .map(|(head, log_prob)| format!("{} ({:.3})", head, log_prob.exp()))
+            .collect::<Vec<_>>();
+
+        let parent = match parent {
+            Some((head, i)) => format!("{}.{}", head, i),
+            None => "<root>".to_string(),
+        };
+
+        println!("{:>8} -> {}", parent, children.join(", "));
+    }
+
+    println!("\nMost probable solutions:");
+    let mut ranked = solutions
+        .iter()
+        .map(|(id, term)| (bigram.log_prob(&ctxt, term), id, term))
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|(p1, ..), (p2, ..)| p2.total_cmp(p1));
+
+    for (log_prob, id, term) in ranked.iter().take(10) {
+        println!("A{:06} ({:>6.2}): {}", id, log_prob, term);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use kolmogorov::*;
+
+mod languages;
+mod utils;
+
+use languages::*;
+use utils::*;
+
+// Compresses the solutions found by `oeis_iterative` into a library, & searches with it.
+// Usage: library_learning [corpus]
+fn main() -> std::io::Result<()> {
+    let path = std::env::args().nth(1);
+    let path = path.as_deref().unwrap_or("data/oeis_iterative");
+
+    let ctxt = Polynomials.context();
+    let solutions = load_solutions(path)?;
+    let corpus = solutions
+        .iter()
+        .map(|(_, term)| term.clone())
+        .collect::<Vec<_>>();
+
+    let (library, rewritten) = compress(&ctxt, &corpus, 5);
+
+    println!("Inventions:");
+    for inv in &library.inventions {
+        println!(
+            "{} = {} : {} (saves {})",
+            inv.name, inv.definition, inv.ty, inv.savings
+        );
+    }
+
+    println!("\nRewritten corpus:");
+    for ((id, term), new) in solutions.iter().zip(&rewritten) {
+        println!(
+            "A{:06} ({:>2} -> {:>2}): {}",
+            id,
+            term.size(),
+            new.size(),
+            new
+        );
+    }
+
+    // The same size budget now reaches terms whose expansions are larger
+    let lang = WithLibrary::new(Polynomials, library);
+    let ty = ty!(N => N => N);
+
+    for size in 1..=8 {
+        let count = search(&Polynomials, vec![], &ty, size).count();
+        let with_library = search(&lang, vec![], &ty, size).count();
+
+        println!(
+            "size {}: {} terms, {} with library",
+            size, count, with_library
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Enumerates list programs until one matches all the examples.
+fn synthesize(lang: &Lists, ty: &Type, examples: &[(Vec<i32>, Term)]) -> Option<Term> {
+    let ctxt = lang.context();
+
+    for n in 1..=12 {
+        'next: for (program, _) in search(lang, vec![], ty, n) {
+            for (input, output) in examples {
+                let input = ListVal(input.iter().map(|x| Term::val(*x)).collect());
+                let prog = term!([program] [:input]);
+
+                match ctxt.evaluate_with(&prog, Budget::default()) {
+                    Ok(out) if out == *output => (),
+                    _ => continue 'next,
+                }
+            }
+
+            return Some(program);
+        }
+    }
+
+    None
+}
+
+fn main() {
+    let lang = Lists;
+
+    let lists = [vec![], vec![3], vec![1, 2], vec![4, 0, 5]];
+
+    let sums = lists
+        .iter()
+        .map(|xs| (xs.clone(), Term::val(xs.iter().sum::<i32>())))
+        .collect::<Vec<_>>();
+
+    let doubled = lists
+        .iter()
+        .map(|xs| {
+            let ys = xs.iter().map(|x| Term::val(2 * x)).collect();
+            (xs.clone(), Term::val(ListVal(ys)))
+        })
+        .collect::<Vec<_>>();
+
+    for (name, ty, examples) in [
+        ("sum", ty!(List N => N), sums),
+        ("double", ty!(List N => List N), doubled),
+    ] {
+        let start = std::time::Instant::now();
+
+        match synthesize(&lang, &ty, &examples) {
+            Some(program) => println!("{} : {} = {}", name, ty, program),
+            None => println!("{} : {} not found", name, ty),
+        }
+
+        println!("Time elapsed: {}s", start.elapsed().as_secs_f32());
+    }
+}
@@ -1,5 +1,5 @@
 use statrs::distribution::{Continuous, Normal};
-use std::rc::Rc;
+use std::sync::Arc;
 
 use kolmogorov::{metro::metropolis, *};
 
@@ -17,7 +17,7 @@ fn main() {
 
     let mut exec_ctxt = lang_ctxt.clone();
 
-    let fibs: Rc<Vec<i32>> = Rc::new((0..num_examples).map(fib).collect());
+    let fibs: Arc<Vec<i32>> = Arc::new((0..num_examples).map(fib).collect());
     let prevs: Vec<(Identifier, BuiltIn)> = (0..num_examples)
         .map(|n| {
             let fibs2 = fibs.clone();
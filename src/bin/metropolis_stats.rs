@@ -0,0 +1,71 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+use std::sync::{Arc, Mutex};
+
+// Logs a run's progress as CSV through the progress callback, then prints its statistics.
+fn main() {
+    let lang = Polynomials;
+    let ctxt = lang.context();
+
+    let target = parse_term("(\\f n -> plus(mult(f)(plus(n)(one)))(mult(n)(n)))").unwrap();
+
+    let mut nums = vec![1i32];
+    for n in 1..8 {
+        let prev = Term::val(*nums.last().unwrap());
+        let next = ctxt.evaluate(&term!([target] [prev] [Term::val(n)]));
+        nums.push(next.get::<i32>());
+    }
+
+    let examples = nums[1..]
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, n)| ((i + 1) as i32, n));
+
+    let log = Arc::new(Mutex::new(vec![]));
+    let sink = log.clone();
+
+    let output = iterative(
+        lang,
+        nums[0],
+        examples,
+        Some(parse_term("(\\f n -> f)").unwrap()),
+        ty!(N => N => N),
+        SynthesisParameters {
+            bias: SizeBias::DistAbs { mean: 20, c: 0.5 },
+            iterations: 20_000,
+            ..Default::default()
+        },
+        Options {
+            print_freq: Some(250),
+            seed: Some(0),
+            progress: Some(Arc::new(move |p: &Progress<'_>| {
+                sink.lock().unwrap().push(format!(
+                    "{},{},{:.4e},{:.2},{}",
+                    p.iteration,
+                    p.size,
+                    p.best_score,
+                    p.stats.mean_size(),
+                    p.stats.distinct_terms()
+                ))
+            })),
+            ..Default::default()
+        },
+    );
+
+    println!("iteration,size,best_score,mean_size,distinct_terms");
+    for line in log.lock().unwrap().iter() {
+        println!("{}", line);
+    }
+
+    println!();
+    output.display();
+
+    println!("\nBest score over time:");
+    for (i, score) in &output.stats.best_trace {
+        println!("  {:>6}: {:.4e}", i, score);
+    }
+}
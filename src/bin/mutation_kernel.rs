@@ -0,0 +1,106 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+use rand::Rng;
+
+// Swaps a builtin for another of exactly the same type.
+// Both directions choose among the same builtin leaves & the same alternatives, so the
+// proposal is symmetric.
+struct SwapBuiltin;
+
+impl<L: Language> Mutation<L> for SwapBuiltin {
+    fn propose(
+        &self,
+        proposer: &mut Proposer<'_, L>,
+        term: &Term,
+        ty: &Type,
+    ) -> Option<(Term, Analysis<L>, f64)> {
+        let ctxt = proposer.ctxt;
+        let meta = proposer.annotate(term, ty);
+
+        let is_builtin = |t: &Term| matches!(t, Term::Var(v) if ctxt.get(*v).is_some());
+
+        let (id, annotation, count) = proposer.random_subnode(term, &meta, |t, _| is_builtin(t));
+
+        if count == 0 {
+            return None;
+        }
+
+        let Term::Var(old) = nth_subterm(term, id) else {
+            unreachable!()
+        };
+
+        let mut alternatives = ctxt
+            .iter()
+            .filter(|(v, builtin)| **v != old && *builtin.ty == annotation.ty)
+            .map(|(v, _)| *v)
+            .collect::<Vec<_>>();
+
+        alternatives.sort();
+
+        if alternatives.is_empty() {
+            return None;
+        }
+
+        let new = alternatives[proposer.rng.gen_range(0..alternatives.len())];
+        let analysis = proposer.lang.svar(new, &annotation.ty);
+
+        let (proposal, analysis) =
+            replace_subnode(proposer.lang, term, &meta, id, Term::Var(new), analysis)?;
+
+        Some((proposal, analysis, 1.))
+    }
+}
+
+// Subterm with the given pre-order index (starting from 1)
+fn nth_subterm(term: &Term, id: usize) -> Term {
+    let mut stack = vec![term];
+    let mut counter = 0;
+
+    while let Some(next) = stack.pop() {
+        counter += 1;
+
+        if counter == id {
+            return next.clone();
+        }
+
+        match next {
+            Term::Lam(_, b) => stack.push(b),
+            Term::App(l, r) => {
+                stack.push(r);
+                stack.push(l);
+            }
+            _ => (),
+        }
+    }
+
+    unreachable!()
+}
+
+// Synthesizes n -> n * n + 1, with builtin swaps mixed into the usual proposals.
+fn main() {
+    let examples = (0..10).map(|n: i32| (n, n * n + 1));
+
+    let kernel = MutationKernel::default().with(0.2, SwapBuiltin);
+
+    let output = simple_map(
+        Polynomials,
+        examples,
+        None,
+        ty!(N => N),
+        SynthesisParameters {
+            iterations: 20_000,
+            ..Default::default()
+        },
+        Options {
+            print_freq: None,
+            seed: Some(0),
+            kernel,
+            ..Default::default()
+        },
+    );
+
+    output.display();
+}
@@ -0,0 +1,36 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Compares the number of terms enumerated with & without observational deduplication.
+fn main() {
+    let lang = Opaque;
+    let ty = ty!(N => N);
+
+    let probes = Probes::new().with(ty!(N), (-2..=2).map(|n: i32| term!([:n])));
+
+    // Reused between sizes, so that larger terms are compared against smaller ones
+    let mut cache = Cache::observing(probes);
+
+    for n in 1..=12 {
+        let start = std::time::Instant::now();
+
+        let all = search(&lang, vec![], &ty, n).count();
+
+        let mut searcher = search_with_cache(&lang, vec![], &ty, n, cache);
+        let observed = searcher
+            .by_ref()
+            .inspect(|(term, _)| println!("{}", term))
+            .count();
+        cache = searcher.cache();
+
+        println!(
+            "Size {:>2}: {:>8} programs, {:>6} observationally distinct ({}s)",
+            n,
+            all,
+            observed,
+            start.elapsed().as_secs_f32()
+        );
+    }
+}
@@ -31,7 +31,10 @@ fn main() -> std::io::Result<()> {
                 bias: SizeBias::DistAbs { mean: 20, c: 0.5 },
                 ..Default::default()
             },
-            Options { print_freq: None },
+            Options {
+                print_freq: None,
+                ..Default::default()
+            },
         );
 
         if output.score.is_none() {
@@ -40,9 +43,10 @@ fn main() -> std::io::Result<()> {
             let term = output.term;
             let analysis = output.analysis;
 
+            // The seed & iteration are enough to replay the run
             let text = format!(
-                "Solution found for A{:06}: {} (≈ {})",
-                id, term, analysis
+                "Solution found for A{:06}: {} (≈ {}) [seed {}, iteration {}]",
+                id, term, analysis, output.seed, output.iterations
             );
 
             println!("{}", text);
@@ -5,11 +5,41 @@ use kolmogorov::*;
 use languages::*;
 use utils::*;
 
+// Usage: oeis_iterative [corpus]
+// Given the solutions of a previous run, weights builtins by how often they were used.
+// Each sequence is checkpointed, so a killed run picks up where it left off when restarted;
+// sequences already solved in the output are skipped.
 fn main() -> std::io::Result<()> {
-    let lang = Polynomials;
+    let corpus = std::env::args().nth(1);
+
+    let (prior, output_path) = match &corpus {
+        Some(path) => {
+            let solutions = load_solutions(path)?;
+            let corpus = solutions.iter().map(|(_, term)| term);
+            let unigram = fit_unigram(&Polynomials.context(), corpus, 1.);
+
+            (unigram.prior(0.5), "data/oeis_iterative_learned")
+        }
+        None => (Prior::default(), "data/oeis_iterative"),
+    };
+
+    let lang = Weighted::new(Polynomials, prior);
     let oeis = oeis::load_oeis_def()?;
 
-    let mut output_file = std::fs::File::create("data/oeis_iterative")?;
+    // Solutions from before a restart are kept
+    let solved = match load_solutions(output_path) {
+        Ok(solutions) => solutions.into_iter().map(|(id, _)| id).collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(e),
+    };
+
+    let mut output_file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output_path)?;
+
+    let checkpoints = format!("{}_checkpoints", output_path);
+    std::fs::create_dir_all(&checkpoints)?;
 
     println!("{} sequences:", oeis.seq.len());
 
@@ -17,6 +47,10 @@ fn main() -> std::io::Result<()> {
     keys.sort();
 
     for id in keys {
+        if solved.contains(id) {
+            continue;
+        }
+
         let nums = &oeis.seq[id];
 
         let examples = nums[1..]
@@ -26,7 +60,7 @@ fn main() -> std::io::Result<()> {
             .map(|(i, n)| ((i + 1) as i32, n));
 
         let output = iterative(
-            lang,
+            lang.clone(),
             nums[0],
             examples,
             None,
@@ -36,7 +70,14 @@ fn main() -> std::io::Result<()> {
                 iterations: 75_000,
                 ..Default::default()
             },
-            Options { print_freq: None },
+            Options {
+                print_freq: None,
+                checkpoint: Some(
+                    Checkpointing::new(format!("{}/A{:06}", checkpoints, id), 5_000)
+                        .with_size_cache(),
+                ),
+                ..Default::default()
+            },
         );
 
         if output.score.is_none() {
@@ -45,9 +86,10 @@ fn main() -> std::io::Result<()> {
             let term = output.term;
             let analysis = output.analysis;
 
+            // The seed & iteration are enough to replay the run
             let text = format!(
-                "Solution found for A{:06}: {} (≈ {})",
-                id, term, analysis
+                "Solution found for A{:06}: {} (≈ {}) [seed {}, iteration {}]",
+                id, term, analysis, output.seed, output.iterations
             );
 
             println!("{}", text);
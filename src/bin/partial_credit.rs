@@ -0,0 +1,56 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// How often each error metric finds a cubic, over a few seeds. With exact scoring, a
+// program which is off by one everywhere is no better than one which is way off.
+// The score factor has to suit the scale of the errors.
+fn main() {
+    let lang = Polynomials;
+    let spec = Examples::map(ty!(N => N), (0..8).map(|x| (x, x * x * x + x * x + 3)));
+
+    let metrics = [
+        ("exact", ErrorMetric::Exact, 0.5),
+        ("absolute", ErrorMetric::Absolute { max: 100. }, 0.01),
+        ("log", ErrorMetric::Log { max: 10. }, 0.2),
+    ];
+
+    for (name, metric, score_factor) in metrics {
+        let mut solved = 0;
+        let mut iterations = 0;
+
+        for seed in 0..10 {
+            let output = synthesize(
+                &lang,
+                &spec,
+                Stochastic {
+                    start: Some(parse_term("(\\x -> x)").unwrap()),
+                    settings: SynthesisParameters {
+                        metric: metric.clone(),
+                        score_factor,
+                        iterations: 20_000,
+                        ..Default::default()
+                    },
+                    options: Options {
+                        print_freq: None,
+                        seed: Some(seed),
+                        ..Default::default()
+                    },
+                },
+            );
+
+            if output.score.is_none() {
+                solved += 1;
+                iterations += output.iterations;
+            }
+        }
+
+        println!(
+            "{:>8}: solved {}/10, in {:.0} iterations on average",
+            name,
+            solved,
+            iterations as f64 / solved.max(1) as f64
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+use std::io::{BufReader, BufWriter};
+use std::time::Instant;
+
+// Run twice: the second run picks up the caches saved by the first.
+fn main() {
+    let lang = Polynomials;
+    let ty = ty!(N => N);
+
+    let dir = std::env::temp_dir().join("kolmogorov_cache");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join(format!("{:016x}.search", context_fingerprint(&lang)));
+
+    let mut cache = Cache::new();
+
+    if path.exists() {
+        let file = BufReader::new(std::fs::File::open(&path).unwrap());
+
+        // E.g. saved by an older version, or with other probes
+        match cache.load(&lang, &vec![], file) {
+            Ok(()) => println!("Loaded search cache from {}", path.display()),
+            Err(err) => println!("Ignoring search cache {}: {}", path.display(), err),
+        }
+    }
+
+    let start = Instant::now();
+
+    for n in 1..=10 {
+        let mut searcher = search_with_cache(&lang, vec![], &ty, n, cache);
+        let count = searcher.by_ref().count();
+        cache = searcher.cache();
+
+        println!("Size {:>2}: {:>6} distinct programs", n, count);
+    }
+
+    println!("Enumerated in {}s", start.elapsed().as_secs_f32());
+
+    let mut file = BufWriter::new(std::fs::File::create(&path).unwrap());
+    cache.save(&lang, &vec![], &mut file).unwrap();
+
+    let examples = [(0, 1), (1, 3), (2, 7), (3, 13), (4, 21)];
+
+    let start = Instant::now();
+
+    let output = simple_map(
+        lang,
+        examples.into_iter(),
+        Some(parse_term("(\\n -> n)").unwrap()),
+        ty.clone(),
+        SynthesisParameters {
+            iterations: 2000,
+            ..Default::default()
+        },
+        Options {
+            print_freq: None,
+            seed: Some(0),
+            cache_dir: Some(dir),
+            ..Default::default()
+        },
+    );
+
+    println!(
+        "Found {} in {}s (sizes counted so far are reused)",
+        output.term,
+        start.elapsed().as_secs_f32()
+    );
+}
@@ -0,0 +1,34 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Enumerates programs using polymorphic builtins, checking that each is well-typed.
+fn main() {
+    let lang = Combinators;
+    let ctxt = lang.context();
+    let ty = ty!(N => N);
+
+    for n in 1..=8 {
+        let mut count = 0;
+
+        for (term, _) in search(&lang, vec![], &ty, n) {
+            let inferred = match infer(&ctxt, &term) {
+                Ok(inferred) => inferred,
+                Err(err) => panic!("`{}` is ill-typed: {}", term, err),
+            };
+
+            if let Err(err) = type_subterms(&ctxt, &term, &ty) {
+                panic!("`{}` was enumerated at the wrong type: {}", term, err);
+            }
+
+            println!("{} : {}", term, inferred);
+            count += 1;
+        }
+
+        println!("There are {} programs of type {} and size {}.", count, ty, n);
+    }
+
+    let iterate = term!(f n -> iter n f zero);
+    println!("{} : {}", iterate, infer(&ctxt, &iterate).unwrap());
+}
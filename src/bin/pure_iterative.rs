@@ -32,7 +32,10 @@ fn main() -> std::io::Result<()> {
                 iterations: 100_000,
                 ..Default::default()
             },
-            Options { print_freq: None },
+            Options {
+                print_freq: None,
+                ..Default::default()
+            },
         );
 
         if output.score.is_none() {
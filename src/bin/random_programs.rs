@@ -0,0 +1,50 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// Random programs of sizes well beyond the reach of enumeration, e.g. for benchmarks.
+fn main() {
+    let lang = Polynomials;
+    let ty = ty!(N => N);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+    for size in [10, 19, 30, 43] {
+        println!(
+            "Size {} (of {} programs):",
+            size,
+            count_terms(&lang, vec![], &ty, size)
+        );
+
+        let samples = sample_terms(&lang, vec![], &ty, size, &mut rng).take(100);
+
+        // Those the analysis rejects would be skipped by the enumerator
+        let kept = samples
+            .filter(|(_, analysis)| !analysis.malformed())
+            .map(|(term, _)| term)
+            .collect::<Vec<_>>();
+
+        for term in kept.iter().take(3) {
+            println!("\t{}", term);
+        }
+
+        println!("\t({} of 100 well-formed)", kept.len());
+    }
+
+    // Drawn in proportion to the prior instead, which favours `plus` over `mult`
+    let weighted = Weighted::new(lang, Prior::new(1.).with_prob("plus", 0.9));
+    let mut counter = Counter::<f64>::new();
+
+    println!("Favouring plus:");
+
+    for _ in 0..3 {
+        let (term, _) = counter
+            .sample(&weighted, vec![], &ty, 22, &mut rng)
+            .unwrap();
+        println!("\t{}", term);
+    }
+}
@@ -23,7 +23,7 @@ fn main() {
     for size in 1.. {
         println!("Time: {}", total_time);
         println!("Searching size {}:", size);
-        'search: for (term, _) in search(&lang, vec![], &targ, size) {
+        'search: for (term, _) in par_search(&lang, vec![], &targ, size) {
             for n in 1..5 {
                 let prev = pow(n - 1);
                 let expected = pow(n);
@@ -44,6 +44,7 @@ fn main() -> std::io::Result<()> {
             },
             Options {
                 print_freq: Some(1),
+                ..Default::default()
             },
         );
 
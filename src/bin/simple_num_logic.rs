@@ -5,6 +5,8 @@ use kolmogorov::*;
 use languages::*;
 use utils::*;
 
+use std::time::Duration;
+
 fn main() -> std::io::Result<()> {
     let lang = NumLogic::new(2);
 
@@ -48,10 +50,18 @@ fn main() -> std::io::Result<()> {
             },
             Options {
                 print_freq: Some(100),
+                // Nested `exists` & `sigma` can make some terms very slow to evaluate
+                limits: Limits {
+                    time: Some(Duration::from_secs(300)),
+                    eval_steps: Some(2_000_000_000),
+                    ..Default::default()
+                },
                 ..Default::default()
             },
         );
 
+        println!("A{:06}: {}", id, output.stats.stop);
+
         if output.score.is_none() {
             use std::io::*;
 
@@ -0,0 +1,30 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// A two-argument map, which none of the dedicated synthesizers handle
+fn main() {
+    let lang = Polynomials;
+
+    let spec = [(0, 0), (1, 2), (2, 3), (3, 1), (4, 4)]
+        .into_iter()
+        .fold(Examples::new(ty!(N => N => N)), |spec, (x, y)| {
+            spec.with(vec![Term::val(x), Term::val(y)], x * y + x)
+        });
+
+    let strategy = Stochastic {
+        start: Some(parse_term("(\\x y -> x)").unwrap()),
+        settings: SynthesisParameters {
+            iterations: 20_000,
+            ..Default::default()
+        },
+        options: Options {
+            print_freq: None,
+            seed: Some(0),
+            ..Default::default()
+        },
+    };
+
+    synthesize(&lang, &spec, strategy).display();
+}
@@ -0,0 +1,76 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Runs a hopeless search (the target is out of reach of `Polynomials`) under each kind of
+// limit, & reports which one ended it.
+fn main() {
+    let lang = Polynomials;
+
+    let nums = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29];
+
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    let limits = [
+        ("iterations", Limits::default()),
+        (
+            "time",
+            Limits {
+                time: Some(Duration::from_millis(200)),
+                ..Default::default()
+            },
+        ),
+        (
+            "evaluation steps",
+            Limits {
+                eval_steps: Some(100_000),
+                ..Default::default()
+            },
+        ),
+        (
+            "cancellation",
+            Limits {
+                cancel: Some(cancel.clone()),
+                ..Default::default()
+            },
+        ),
+    ];
+
+    for (name, limits) in limits {
+        if let Some(cancel) = limits.cancel.clone() {
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(300));
+                cancel.store(true, Ordering::Relaxed);
+            });
+        }
+
+        let examples = nums.iter().cloned().enumerate().map(|(i, n)| (i as i32, n));
+
+        let output = simple_map(
+            lang,
+            examples,
+            Some(parse_term("(\\n -> n)").unwrap()),
+            ty!(N => N),
+            SynthesisParameters {
+                iterations: 20_000,
+                ..Default::default()
+            },
+            Options {
+                print_freq: None,
+                seed: Some(0),
+                limits,
+                ..Default::default()
+            },
+        );
+
+        println!(
+            "Limited by {}: {} after {} iterations in {:.3}s",
+            name, output.stats.stop, output.iterations, output.time
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use kolmogorov::*;
+
+// Solutions written by `oeis_iterative`, as (sequence id, term)
+#[allow(dead_code)]
+pub fn load_solutions(path: &str) -> std::io::Result<Vec<(usize, Term)>> {
+    use std::io::{Error, ErrorKind};
+
+    const PREFIX: &str = "Solution found for A";
+
+    let file = std::fs::read_to_string(path)?;
+    let mut solutions = vec![];
+
+    for line in file.lines() {
+        let Some(rest) = line.strip_prefix(PREFIX) else {
+            continue;
+        };
+
+        let (name, rest) = rest.split_once(": ").ok_or(ErrorKind::InvalidData)?;
+
+        // The analysis follows the term, but may contain parentheses itself
+        let src = match rest.find(" (≈ ") {
+            Some(end) => &rest[..end],
+            None => rest,
+        };
+
+        let id = name
+            .parse::<usize>()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        let term = parse_term(src).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        solutions.push((id, term));
+    }
+
+    Ok(solutions)
+}
@@ -1,5 +1,7 @@
 #![allow(unused_imports)]
 
+pub mod corpus;
 pub mod oeis;
 
+pub use corpus::*;
 pub use oeis::*;
@@ -0,0 +1,80 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Squaring, where the first two examples are also fit by the identity. Trained on those
+// alone, both strategies settle for the identity; holding out the rest rules it out.
+fn main() {
+    let lang = Polynomials;
+
+    let spec = Examples::map(ty!(N => N), (0..5).map(|x| (x, x * x)));
+    // Unvalidated runs only get to see the training examples
+    let (training, held_out) = split(&spec, 3).unwrap();
+
+    let options = || Options {
+        print_freq: None,
+        seed: Some(0),
+        ..Default::default()
+    };
+
+    for (name, validation) in [
+        ("Unvalidated", None),
+        (
+            "Validated",
+            Some(Validation {
+                held_out: 3,
+                shorten: false,
+            }),
+        ),
+        (
+            "Shortened",
+            Some(Validation {
+                held_out: 3,
+                shorten: true,
+            }),
+        ),
+    ] {
+        let settings = || SynthesisParameters {
+            iterations: 20_000,
+            validation: validation.clone(),
+            ..Default::default()
+        };
+
+        let enumerated = match validation {
+            None => enumerative_synthesize(lang, &training, Some(20), settings(), options()),
+            Some(_) => enumerative_synthesize(lang, &spec, Some(20), settings(), options()),
+        }
+        .expect("There are programs up to size 20");
+
+        let strategy = Stochastic {
+            start: Some(parse_term("(\\x -> x)").unwrap()),
+            settings: settings(),
+            options: options(),
+        };
+
+        let sampled = match validation {
+            None => synthesize(&lang, &training, strategy),
+            Some(_) => synthesize(&lang, &spec, strategy),
+        };
+
+        println!("{}:", name);
+
+        for (strategy, output) in [("Enumeration", enumerated), ("Metropolis", sampled)] {
+            let generalized =
+                held_out.num_correct(&lang.context(), &output.term, Budget::default());
+            let overfit = output.validation.map_or(0, |report| report.overfit);
+
+            println!(
+                "{:>13}: {} ({}/2 training, {}/3 held out, {}) after {} iterations ({} overfit)",
+                strategy,
+                output.term,
+                output.num_correct,
+                generalized,
+                output.stats.stop,
+                output.iterations,
+                overfit,
+            );
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use kolmogorov::*;
+
+mod languages;
+use languages::*;
+
+// Enumerates in order of description length, where `plus` is common & `mult` is rare.
+fn main() {
+    let prior = Prior::new(0.5)
+        .with_prob("plus", 0.4)
+        .with_prob("mult", 0.02)
+        .with_prob("one", 0.3)
+        .with_prob("zero", 0.1);
+
+    let lang = Weighted::new(Opaque, prior.clone());
+    let ty = ty!(N => N);
+
+    let mut found = 0;
+
+    for cost in 1.. {
+        for (term, _) in search(&lang, vec![], &ty, cost) {
+            println!(
+                "cost {:>2} (log-prob {:>6.2}): {}",
+                cost,
+                prior.log_prob(&term),
+                term
+            );
+            found += 1;
+        }
+
+        if found >= 20 {
+            break;
+        }
+    }
+}
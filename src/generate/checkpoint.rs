@@ -0,0 +1,162 @@
+// Saving & resuming long runs.
+// Checkpoints are plain text, one field per line, with terms as printed (& parsed back with
+// the default literals). Replaying from the seed & position of the RNG makes a resumed run
+// propose exactly what the uninterrupted run would have.
+
+use super::*;
+
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// Only `tempered_metropolis` (& so `metropolis`, & synthesizers sampling with it) checkpoints;
+// `anneal` & `genetic` panic if given a checkpoint.
+#[derive(Clone, Debug)]
+pub struct Checkpointing {
+    // Runs resume from here if it exists
+    pub path: PathBuf,
+    // Iterations between saves
+    pub interval: usize,
+    // Also save the masses computed by the size cache (next to `path`, as `.sizes`)
+    pub size_cache: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub iteration: usize,
+    pub seed: u64,
+    pub word_pos: u128, // Of the RNG
+    pub best: (Term, f64),
+    pub chains: Vec<(Term, f64, ChainReport)>,
+}
+
+impl Checkpointing {
+    pub fn new(path: impl Into<PathBuf>, interval: usize) -> Self {
+        Self {
+            path: path.into(),
+            interval,
+            size_cache: false,
+        }
+    }
+
+    pub fn with_size_cache(self) -> Self {
+        Self {
+            size_cache: true,
+            ..self
+        }
+    }
+
+    pub fn size_cache_path(&self) -> PathBuf {
+        self.path.with_extension("sizes")
+    }
+
+    // The checkpoint to resume from, if any
+    pub fn resume(&self) -> io::Result<Option<Checkpoint>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        Checkpoint::load(&self.path).map(Some)
+    }
+}
+
+impl Checkpoint {
+    // Written to a temporary file first, so that a kill mid-save leaves the old one intact
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+
+        let mut out = BufWriter::new(std::fs::File::create(&tmp)?);
+
+        writeln!(out, "iteration {}", self.iteration)?;
+        writeln!(out, "seed {}", self.seed)?;
+        writeln!(out, "word_pos {}", self.word_pos)?;
+        writeln!(out, "best {} {}", self.best.1, self.best.0)?;
+
+        for (term, score, report) in &self.chains {
+            writeln!(
+                out,
+                "chain {} {} {} {} {} {} {} {}",
+                report.temperature,
+                score,
+                report.proposals,
+                report.accepted,
+                report.swaps_proposed,
+                report.swaps_accepted,
+                report.restarts,
+                term
+            )?;
+        }
+
+        out.flush()?;
+        drop(out);
+
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |line: &str| io::Error::new(io::ErrorKind::InvalidData, line.to_string());
+
+        let mut iteration = None;
+        let mut seed = None;
+        let mut word_pos = None;
+        let mut best = None;
+        let mut chains = vec![];
+
+        for line in BufReader::new(std::fs::File::open(path)?).lines() {
+            let line = line?;
+
+            let (key, rest) = line.split_once(' ').ok_or_else(|| invalid(&line))?;
+
+            // The numbers before a term
+            let fields = |n: usize| -> io::Result<(Vec<&str>, Term)> {
+                let mut parts = rest.splitn(n + 1, ' ').collect::<Vec<_>>();
+                let term = parts.pop().filter(|_| parts.len() == n);
+                let term = term.and_then(|t| parse_term(t).ok());
+
+                Ok((parts, term.ok_or_else(|| invalid(&line))?))
+            };
+
+            match key {
+                "iteration" => iteration = rest.parse().ok(),
+                "seed" => seed = rest.parse().ok(),
+                "word_pos" => word_pos = rest.parse().ok(),
+                "best" => {
+                    let (nums, term) = fields(1)?;
+                    best = Some((term, nums[0].parse().map_err(|_| invalid(&line))?));
+                }
+                "chain" => {
+                    let (nums, term) = fields(7)?;
+
+                    let float = |s: &str| s.parse::<f64>().map_err(|_| invalid(&line));
+                    let int = |s: &str| s.parse::<usize>().map_err(|_| invalid(&line));
+
+                    let report = ChainReport {
+                        temperature: float(nums[0])?,
+                        proposals: int(nums[2])?,
+                        accepted: int(nums[3])?,
+                        swaps_proposed: int(nums[4])?,
+                        swaps_accepted: int(nums[5])?,
+                        restarts: int(nums[6])?,
+                    };
+
+                    chains.push((term, float(nums[1])?, report));
+                }
+                _ => return Err(invalid(&line)),
+            }
+        }
+
+        let missing = |field: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("checkpoint is missing `{}`", field),
+            )
+        };
+
+        Ok(Self {
+            iteration: iteration.ok_or_else(|| missing("iteration"))?,
+            seed: seed.ok_or_else(|| missing("seed"))?,
+            word_pos: word_pos.ok_or_else(|| missing("word_pos"))?,
+            best: best.ok_or_else(|| missing("best"))?,
+            chains,
+        })
+    }
+}
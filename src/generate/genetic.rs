@@ -0,0 +1,338 @@
+// Genetic programming: a population evolved by tournament selection, subtree crossover &
+// the mutations of a `MutationKernel`. Unlike `metropolis`, nothing here is a sampler; we
+// only keep track of the best term found. Individuals are selected by their score, so any
+// size bias is the scorer's (e.g. `SynthesisParameters::bias`).
+
+use super::*;
+
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+#[derive(Clone, Debug)]
+pub struct Genetic {
+    pub population: usize,
+    // Individuals drawn per tournament (more means stronger selection)
+    pub tournament_size: usize,
+    // Probability an offspring is bred by crossover, rather than by mutation
+    pub crossover_rate: f64,
+    // Best individuals carried over unchanged to the next generation
+    pub elitism: usize,
+}
+
+impl Default for Genetic {
+    fn default() -> Self {
+        Self {
+            population: 100,
+            tournament_size: 4,
+            crossover_rate: 0.7,
+            elitism: 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GeneticReport {
+    pub generations: usize,
+    pub crossovers: usize,
+    pub mutations: usize,
+    // Offspring that couldn't be bred (no compatible subterms, ill-formed results, ...)
+    pub failures: usize,
+}
+
+#[derive(Clone)]
+struct Individual<L: Language> {
+    term: Term,
+    analysis: Analysis<L>,
+    score: f64,
+}
+
+// Each iteration breeds (or tries to breed) a single offspring, so `iterations` bounds the
+// number of calls to the scorer, as for `metropolis`. The population starts out as `start`
+// & its mutants (the first generation). If F returns None, we stop immediately.
+pub fn genetic<F: FnMut(&Term) -> Option<f64>, L: Language>(
+    lang: &L,
+    start: &Term,
+    ty: &Type,
+    mut scorer: F,
+    iterations: usize,
+    options: Options<L>,
+    genetic: &Genetic,
+) -> (usize, Term, Analysis<L>, GeneticReport, Stats) {
+    assert!(
+        genetic.elitism < genetic.population,
+        "Elites must leave room for offspring"
+    );
+
+    // Their state isn't saved, so a restarted run would silently start over
+    assert!(
+        options.checkpoint.is_none(),
+        "Genetic runs can't be checkpointed"
+    );
+
+    let mut i = 0;
+    let mut report = GeneticReport::default();
+    let mut stats = Stats::new(&options.kernel);
+    let watch = options.limits.watch();
+
+    let ctxt = lang.context();
+    let prior = lang.prior();
+
+    let mut cache = options.size_cache(lang);
+    let mut rng = options.rng();
+
+    let Some(score) = scorer(start) else {
+        stats.stop = StopReason::Solved;
+        return (i, start.clone(), Analysis::Unique, report, stats);
+    };
+
+    stats.improved(i, score);
+    stats.visit(start);
+
+    let mut best = Individual {
+        term: start.clone(),
+        analysis: Analysis::Unique,
+        score,
+    };
+
+    let mut population = vec![best.clone()];
+    let mut next = vec![];
+
+    while i < iterations {
+        if let Some(reason) = watch.check() {
+            stats.stop = reason;
+            break;
+        }
+
+        i += 1;
+
+        if let Some(freq) = options.print_freq {
+            if i % freq == 0 {
+                let progress = Progress {
+                    iteration: i,
+                    iterations,
+                    size: best.term.size(),
+                    best_score: best.score,
+                    stats: &stats,
+                };
+
+                options.report(progress, |p| {
+                    println!(
+                        "Genetic progress: {}/{}. Generation {}. Best size {}",
+                        p.iteration, p.iterations, report.generations, p.size
+                    )
+                });
+            }
+        }
+
+        let mut proposer = Proposer::new(lang, &ctxt, &prior, &mut rng, &mut cache);
+
+        // The initial population is filled by a random walk from the start
+        let offspring = if report.generations == 0 {
+            report.mutations += 1;
+
+            let parent = population[proposer.rng.gen_range(0..population.len())]
+                .term
+                .clone();
+
+            mutate(&options.kernel, &mut proposer, &parent, ty, &mut stats)
+        } else if with_probability(proposer.rng, genetic.crossover_rate) {
+            report.crossovers += 1;
+
+            let mother = tournament(proposer.rng, &population, genetic.tournament_size);
+            let father = tournament(proposer.rng, &population, genetic.tournament_size);
+
+            crossover(&mut proposer, &mother.term, &father.term, ty)
+        } else {
+            report.mutations += 1;
+
+            let parent = tournament(proposer.rng, &population, genetic.tournament_size);
+
+            mutate(&options.kernel, &mut proposer, &parent.term, ty, &mut stats)
+        };
+
+        if let Some((term, analysis)) = offspring {
+            let Some(score) = scorer(&term) else {
+                report.generations += 1;
+                stats.stop = StopReason::Solved;
+                return (i, term, analysis, report, stats);
+            };
+
+            stats.improved(i, score);
+            stats.visit(&term);
+            stats.step(term.size());
+
+            let individual = Individual {
+                term,
+                analysis,
+                score,
+            };
+
+            if individual.score > best.score {
+                best = individual.clone();
+            }
+
+            if report.generations == 0 {
+                population.push(individual);
+            } else {
+                next.push(individual);
+            }
+        } else {
+            report.failures += 1;
+        }
+
+        // The start counts towards the initial population, which may already be full
+        if report.generations == 0 && population.len() >= genetic.population {
+            report.generations += 1;
+        }
+
+        if next.len() + genetic.elitism.min(population.len()) >= genetic.population {
+            population.sort_by(|a, b| b.score.total_cmp(&a.score));
+            population.truncate(genetic.elitism);
+            population.append(&mut next);
+
+            report.generations += 1;
+        }
+    }
+
+    (i, best.term, best.analysis, report, stats)
+}
+
+// Offspring always enter the population, so every proposal counts as accepted
+fn mutate<L: Language>(
+    kernel: &MutationKernel<L>,
+    proposer: &mut Proposer<'_, L>,
+    term: &Term,
+    ty: &Type,
+    stats: &mut Stats,
+) -> Option<(Term, Analysis<L>)> {
+    let (kind, proposal) = kernel.propose_indexed(proposer, term, ty)?;
+
+    let Some((proposal, analysis, _)) = proposal else {
+        stats.mutations[kind].failures += 1;
+        return None;
+    };
+
+    stats.mutations[kind].proposals += 1;
+    stats.mutations[kind].accepted += 1;
+
+    Some((proposal, analysis))
+}
+
+// The fittest of `size` individuals, drawn with replacement
+fn tournament<'a, L: Language>(
+    rng: &mut ChaCha8Rng,
+    population: &'a [Individual<L>],
+    size: usize,
+) -> &'a Individual<L> {
+    (0..size.max(1))
+        .map(|_| &population[rng.gen_range(0..population.len())])
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .unwrap()
+}
+
+// Replaces a random subterm of `mother` with a random subterm of `father` of the same type,
+// whose free variables are all in scope (with the same types) at the replaced position.
+pub fn crossover<L: Language>(
+    proposer: &mut Proposer<'_, L>,
+    mother: &Term,
+    father: &Term,
+    ty: &Type,
+) -> Option<(Term, Analysis<L>)> {
+    let mother_meta = proposer.annotate(mother, ty);
+    let father_meta = proposer.annotate(father, ty);
+
+    let ctxt = proposer.ctxt;
+
+    let (node, annotation, _) = proposer.random_subnode(mother, &mother_meta, |_, _| true);
+
+    let (donor, _, count) = proposer.random_subnode(father, &father_meta, |t, a| {
+        a.ty == annotation.ty && fits(ctxt, t, &father_meta, &a.decls, &annotation.decls)
+    });
+
+    if count == 0 {
+        return None;
+    }
+
+    let donor = subnode(father, donor);
+    let analysis = analyze_term(proposer.lang, donor, &father_meta);
+
+    let (offspring, analysis) = replace_subnode(
+        proposer.lang,
+        mother,
+        &mother_meta,
+        node,
+        donor.clone(),
+        analysis,
+    )?;
+
+    if analysis.malformed() || !offspring.in_beta_normal_form() {
+        return None;
+    }
+
+    Some((offspring, analysis))
+}
+
+// Whether `term`, declared under `from`, may be moved under `to`
+fn fits(ctxt: &Context, term: &Term, meta: &Metadata, from: &VarsVec, to: &VarsVec) -> bool {
+    fn helper(
+        ctxt: &Context,
+        term: &Term,
+        meta: &Metadata,
+        from: &VarsVec,
+        to: &VarsVec,
+        bound: &mut Vec<Identifier>,
+    ) -> bool {
+        use Term::*;
+        match term {
+            Val(_) => true,
+            Var(v) if bound.contains(v) => true,
+            Var(v) => {
+                let ty = &meta[&(term as *const Term)].ty;
+                let declared = |decls: &VarsVec| decls.iter().rev().find(|(u, _)| u == v).cloned();
+
+                match (declared(from), declared(to)) {
+                    (Some(_), Some((_, to_ty))) => *to_ty == *ty,
+                    (None, None) => ctxt.get(*v).is_some(),
+                    _ => false,
+                }
+            }
+            Lam(v, b) => {
+                bound.push(*v);
+                let fits = helper(ctxt, b, meta, from, to, bound);
+                bound.pop();
+                fits
+            }
+            App(l, r) => {
+                helper(ctxt, l, meta, from, to, bound) && helper(ctxt, r, meta, from, to, bound)
+            }
+        }
+    }
+
+    helper(ctxt, term, meta, from, to, &mut vec![])
+}
+
+// The subterm with the given (pre-order) id, as numbered by `random_subnode`
+fn subnode(term: &Term, id: usize) -> &Term {
+    let mut stack = vec![term];
+    let mut counter = 1;
+
+    while let Some(next) = stack.pop() {
+        if counter == id {
+            return next;
+        }
+
+        use Term::*;
+        match next {
+            Lam(_, b) => stack.push(b),
+            App(l, r) => {
+                stack.push(r);
+                stack.push(l);
+            }
+            _ => (),
+        }
+
+        counter += 1;
+    }
+
+    panic!("No subterm {} in {}", id, term)
+}
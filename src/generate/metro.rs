@@ -1,30 +1,254 @@
 use super::*;
 
-use rustc_hash::FxHashMap as HashMap;
-use statrs::distribution::Discrete;
-
-// Probability of replacing a variable with another
-const REPLACE_VAR: f64 = 0.5;
-// Probability of replacing a small (non-variable) subterm with another of equal size
-const REPLACE_SMALL: f64 = 0.40;
-// Probability of replacing a larger subterm with anohter, potentially of different size
-// This is much more computationally expensive and can erase a lot of progress, but also
-// allows us to exit local minima (we must calculate g(x'|x) & g(x|x'), involving a census
-// of terms we don't even use, so it should be much more unlikely than the others).
-#[allow(unused)]
-const REPLACE_LARGE: f64 = 1. - REPLACE_VAR - REPLACE_SMALL;
-
-#[derive(Clone, Copy)]
-pub struct Options {
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet, FxHasher};
+use std::fmt::{self, Display};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+pub struct Options<L: Language> {
     pub print_freq: Option<usize>, // How often we print out progress, if at all
+    // Runs with the same seed (& language) make the same proposals. Random if unset.
+    pub seed: Option<u64>,
+    pub kernel: MutationKernel<L>,
+    // Receives progress every `print_freq` iterations, instead of stdout
+    pub progress: Option<ProgressCallback>,
+    pub limits: Limits,
+    pub checkpoint: Option<Checkpointing>,
+    // The masses of size classes are loaded from (& saved back to) a file in here named
+    // after the language's `context_fingerprint` (& `count_masses`), so later runs can skip
+    // counting them
+    pub cache_dir: Option<PathBuf>,
+    // Count the masses of size classes (for the proposal probabilities of large replacements)
+    // with a `Counter`, rather than by enumerating them. This is much faster, but only exact
+    // if the language's analysis doesn't prune anything.
+    pub count_masses: bool,
 }
 
-impl Default for Options {
+// Besides the number of iterations. These are checked between iterations, so a single
+// slow evaluation can overrun them (see `Budget` for per-evaluation limits).
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    pub time: Option<Duration>,
+    // Over all evaluations during the run (see `evaluation_steps`)
+    pub eval_steps: Option<usize>,
+    // Stops the run once set, e.g. from another thread
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+// What ended a run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StopReason {
+    Solved,
+    #[default]
+    Iterations,
+    Time,
+    EvalSteps,
+    Cancelled,
+    // Every program (up to the maximum size) was enumerated
+    Exhausted,
+}
+
+pub(super) struct Watch<'a> {
+    limits: &'a Limits,
+    started: Instant,
+    steps: usize,
+}
+
+pub type ProgressCallback = Arc<dyn Fn(&Progress<'_>) + Send + Sync>;
+
+pub struct Progress<'a> {
+    pub iteration: usize,
+    pub iterations: usize,
+    pub size: usize, // Of the current term (of the coldest chain)
+    pub best_score: f64,
+    pub stats: &'a Stats,
+}
+
+// Collected over all chains
+#[derive(Clone, Default)]
+pub struct Stats {
+    pub mutations: Vec<MutationStats>,
+    // (iteration, score) whenever the best score improves
+    pub best_trace: Vec<(usize, f64)>,
+    pub stop: StopReason,
+    steps: usize,
+    total_size: usize,
+    visited: HashSet<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MutationStats {
+    pub name: String,
+    pub proposals: usize,
+    pub accepted: usize,
+    // Mutations which couldn't propose anything
+    pub failures: usize,
+}
+
+impl<L: Language> Default for Options<L> {
     fn default() -> Self {
         Self {
             print_freq: Some(100),
+            seed: None,
+            kernel: MutationKernel::default(),
+            progress: None,
+            limits: Limits::default(),
+            checkpoint: None,
+            cache_dir: None,
+            count_masses: false,
+        }
+    }
+}
+
+impl<L: Language> Options<L> {
+    // Fixes a random seed if none was given, so that the run can be replayed.
+    // Runs resuming from a checkpoint take its seed instead.
+    pub fn seeded(self) -> Self {
+        let resumed = self.checkpoint.as_ref().and_then(|c| c.resume().ok()?);
+
+        Self {
+            seed: Some(match resumed {
+                Some(checkpoint) => checkpoint.seed,
+                None => self.seed.unwrap_or_else(rand::random),
+            }),
+            ..self
+        }
+    }
+
+    pub fn rng(&self) -> ChaCha8Rng {
+        match self.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        }
+    }
+
+    pub(super) fn size_cache(&self, lang: &L) -> SizeCache<L> {
+        let mut cache = SizeCache::new(lang);
+
+        if self.count_masses {
+            cache.counter = Some(Counter::new());
+        }
+
+        if let Some(dir) = &self.cache_dir {
+            // Counted masses may include terms the analysis would prune, so they're kept
+            // apart from enumerated ones
+            let ext = if self.count_masses { "counts" } else { "sizes" };
+            let path = dir.join(format!("{:016x}.{}", cache.fingerprint, ext));
+
+            cache.load(&path);
+            cache.persist = Some(path);
+        }
+
+        cache
+    }
+
+    // Calls back if there's a callback, otherwise prints with `print`
+    pub fn report(&self, progress: Progress<'_>, print: impl FnOnce(&Progress<'_>)) {
+        match &self.progress {
+            Some(callback) => callback(&progress),
+            None => print(&progress),
+        }
+    }
+}
+
+impl Limits {
+    pub(super) fn watch(&self) -> Watch<'_> {
+        Watch {
+            limits: self,
+            started: Instant::now(),
+            steps: evaluation_steps(),
+        }
+    }
+}
+
+impl Watch<'_> {
+    pub(super) fn check(&self) -> Option<StopReason> {
+        let Limits {
+            time,
+            eval_steps,
+            cancel,
+        } = self.limits;
+
+        if cancel.as_ref().is_some_and(|c| c.load(Ordering::Relaxed)) {
+            Some(StopReason::Cancelled)
+        } else if time.is_some_and(|t| self.started.elapsed() >= t) {
+            Some(StopReason::Time)
+        } else if eval_steps.is_some_and(|n| evaluation_steps() - self.steps >= n) {
+            Some(StopReason::EvalSteps)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use StopReason::*;
+        match self {
+            Solved => write!(f, "solved"),
+            Iterations => write!(f, "out of iterations"),
+            Time => write!(f, "out of time"),
+            EvalSteps => write!(f, "out of evaluation steps"),
+            Cancelled => write!(f, "cancelled"),
+            Exhausted => write!(f, "out of programs"),
+        }
+    }
+}
+
+impl Stats {
+    pub fn new<L: Language>(kernel: &MutationKernel<L>) -> Self {
+        Self {
+            mutations: kernel
+                .names()
+                .into_iter()
+                .map(|name| MutationStats {
+                    name,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    // Average size of the current term(s), over all iterations
+    pub fn mean_size(&self) -> f64 {
+        self.total_size as f64 / self.steps.max(1) as f64
+    }
+
+    // Number of distinct terms the chains have been in
+    pub fn distinct_terms(&self) -> usize {
+        self.visited.len()
+    }
+
+    pub(super) fn improved(&mut self, iteration: usize, score: f64) {
+        let improves = match self.best_trace.last() {
+            Some(&(_, best)) => score > best,
+            None => true,
+        };
+
+        if improves {
+            self.best_trace.push((iteration, score));
         }
     }
+
+    pub(super) fn visit(&mut self, term: &Term) {
+        // Terms aren't hashable, but their printed forms are
+        let mut hasher = FxHasher::default();
+        term.to_string().hash(&mut hasher);
+        self.visited.insert(hasher.finish());
+    }
+
+    pub(super) fn step(&mut self, size: usize) {
+        self.steps += 1;
+        self.total_size += size;
+    }
 }
 
 // If F returns None, we stop immediately
@@ -32,234 +256,539 @@ pub fn metropolis<F: FnMut(&Term) -> Option<f64>, L: Language>(
     lang: &L,
     start: &Term,
     ty: &Type,
-    mut scorer: F,
+    scorer: F,
     iterations: usize,
-    options: Options,
+    options: Options<L>,
 ) -> (usize, Term, Analysis<L>) {
+    let tempering = Tempering::default();
+
+    let (i, term, analysis, _, _) =
+        tempered_metropolis(lang, start, ty, scorer, iterations, options, &tempering);
+
+    (i, term, analysis)
+}
+
+// Chains at higher temperatures flatten the score, so they move more freely between
+// local optima, & pass good states down to the colder chains by swapping.
+#[derive(Clone, Debug)]
+pub struct Tempering {
+    // In increasing order. The first chain (usually at 1) is the one being sampled.
+    pub temperatures: Vec<f64>,
+    // Iterations between rounds of swaps (0 for none)
+    pub swap_interval: usize,
+}
+
+impl Tempering {
+    // `n` temperatures from 1 to `max`, in a geometric progression
+    pub fn geometric(n: usize, max: f64, swap_interval: usize) -> Self {
+        assert!(n > 0, "Tempering needs at least one chain");
+
+        let ratio = max.powf(1. / (n.max(2) - 1) as f64);
+
+        Self {
+            temperatures: (0..n).map(|k| ratio.powi(k as i32)).collect(),
+            swap_interval,
+        }
+    }
+}
+
+impl Default for Tempering {
+    fn default() -> Self {
+        Self {
+            temperatures: vec![1.],
+            swap_interval: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ChainReport {
+    pub temperature: f64,
+    pub proposals: usize,
+    pub accepted: usize,
+    pub swaps_proposed: usize,
+    pub swaps_accepted: usize,
+    pub restarts: usize,
+}
+
+impl ChainReport {
+    pub fn acceptance_rate(&self) -> f64 {
+        self.accepted as f64 / self.proposals.max(1) as f64
+    }
+
+    pub fn swap_rate(&self) -> f64 {
+        self.swaps_accepted as f64 / self.swaps_proposed.max(1) as f64
+    }
+}
+
+// Runs one chain per temperature, in lockstep (each iteration steps every chain).
+// Returns the best term over all chains.
+pub fn tempered_metropolis<F: FnMut(&Term) -> Option<f64>, L: Language>(
+    lang: &L,
+    start: &Term,
+    ty: &Type,
+    mut scorer: F,
+    iterations: usize,
+    options: Options<L>,
+    tempering: &Tempering,
+) -> (usize, Term, Analysis<L>, Vec<ChainReport>, Stats) {
+    // Checkpoints need a seed to replay
+    let options = options.seeded();
+
     let mut i = 0;
-    let mut candidate = start.clone();
+    let mut stats = Stats::new(&options.kernel);
+    let watch = options.limits.watch();
+
+    let mut chains = tempering
+        .temperatures
+        .iter()
+        .map(|&temperature| Chain {
+            candidate: start.clone(),
+            score: 0.,
+            report: ChainReport {
+                temperature,
+                ..Default::default()
+            },
+        })
+        .collect::<Vec<_>>();
+
+    let reports = |chains: Vec<Chain>| chains.into_iter().map(|c| c.report).collect();
 
-    let mut best_candidate = start.clone();
-    let mut best_score = 0.;
     // Technically, we should probably perform some analysis here.
-    let mut best_analysis = Analysis::Unique;
+    let mut best = Best {
+        term: start.clone(),
+        score: 0.,
+        analysis: Analysis::Unique,
+    };
 
-    let Some(mut score) = scorer(start) else {
-        return (i, candidate, best_analysis);
+    let Some(score) = scorer(start) else {
+        stats.stop = StopReason::Solved;
+        return (i, best.term, best.analysis, reports(chains), stats);
     };
 
-    let mut cache = SizeCache::default();
+    for chain in &mut chains {
+        chain.score = score;
+    }
+
+    stats.improved(i, score);
+    stats.visit(start);
+
+    let ctxt = lang.context();
+    let prior = lang.prior();
+
+    let mut cache = options.size_cache(lang);
+    let mut rng = options.rng();
+
+    if let Some(checkpointing) = &options.checkpoint {
+        i = resume(
+            lang,
+            ty,
+            checkpointing,
+            &mut chains,
+            &mut best,
+            &mut rng,
+            &mut cache,
+        );
+    }
 
     while i < iterations {
+        if let Some(reason) = watch.check() {
+            stats.stop = reason;
+            break;
+        }
+
         i += 1;
 
         if let Some(freq) = options.print_freq {
             if i % freq == 0 {
-                println!(
-                    "Metropolis progress: {}/{}. Size {}",
-                    i,
+                let progress = Progress {
+                    iteration: i,
                     iterations,
-                    candidate.size()
-                );
+                    size: chains[0].candidate.size(),
+                    best_score: best.score,
+                    stats: &stats,
+                };
+
+                options.report(progress, |p| {
+                    println!(
+                        "Metropolis progress: {}/{}. Size {}",
+                        p.iteration, p.iterations, p.size
+                    )
+                });
             }
         }
 
-        // g_ratio = g(x|x') / g(x'|x)
-        let Some((proposal, analysis, g_ratio)) =
-            mutate(lang, &candidate, ty, &mut cache)
-        else {
-            continue;
-        };
+        for chain in &mut chains {
+            let mut proposer = Proposer::new(lang, &ctxt, &prior, &mut rng, &mut cache);
+
+            if let Some((term, analysis)) = chain.step(
+                &options.kernel,
+                &mut proposer,
+                &mut scorer,
+                ty,
+                &mut best,
+                &mut stats,
+            ) {
+                stats.stop = StopReason::Solved;
+                return (i, term, analysis, reports(chains), stats);
+            }
 
-        let Some(proposal_score) = scorer(&proposal) else {
-            return (i, proposal, analysis);
-        };
+            stats.improved(i, best.score);
+            stats.step(chain.candidate.size());
+        }
+
+        if i.checked_rem(tempering.swap_interval) == Some(0) {
+            for k in 1..chains.len() {
+                let (cold, hot) = chains.split_at_mut(k);
+                let (cold, hot) = (&mut cold[k - 1], &mut hot[0]);
+
+                // Ratio of the joint probabilities after & before the swap
+                let exponent = 1. / cold.report.temperature - 1. / hot.report.temperature;
+                let swap_prob = (hot.score / cold.score).powf(exponent);
+
+                cold.report.swaps_proposed += 1;
+                hot.report.swaps_proposed += 1;
 
-        if proposal_score > best_score {
-            best_analysis = analysis;
-            best_score = proposal_score;
-            best_candidate = proposal.clone();
+                if with_probability(&mut rng, swap_prob) {
+                    std::mem::swap(&mut cold.candidate, &mut hot.candidate);
+                    std::mem::swap(&mut cold.score, &mut hot.score);
+
+                    cold.report.swaps_accepted += 1;
+                    hot.report.swaps_accepted += 1;
+                }
+            }
         }
 
-        let score_ratio = proposal_score / score;
+        if let Some(checkpointing) = &options.checkpoint {
+            if i.checked_rem(checkpointing.interval) == Some(0) {
+                let checkpoint = Checkpoint {
+                    iteration: i,
+                    seed: options.seed.unwrap(),
+                    word_pos: rng.get_word_pos(),
+                    best: (best.term.clone(), best.score),
+                    chains: chains
+                        .iter()
+                        .map(|c| (c.candidate.clone(), c.score, c.report.clone()))
+                        .collect(),
+                };
 
-        let acceptance_prob = score_ratio * g_ratio;
+                // A failed save shouldn't cost us the run
+                if let Err(err) = save(checkpointing, &checkpoint, &cache) {
+                    eprintln!("Failed to save checkpoint: {}", err);
+                }
+            }
+        }
+    }
 
-        if with_probability(acceptance_prob) {
-            candidate = proposal;
-            score = proposal_score;
+    (i, best.term, best.analysis, reports(chains), stats)
+}
+
+// Temperature over the course of a run, for annealing
+#[derive(Clone, Copy, Debug)]
+pub enum Schedule {
+    Constant(f64),
+    // From `start` at the first iteration to `end` at the last
+    Linear { start: f64, end: f64 },
+    // start * decay^i
+    Exponential { start: f64, decay: f64 },
+    // Decays from `max` to `min` along a half cosine, then jumps back up every `period`
+    CosineRestarts { max: f64, min: f64, period: usize },
+}
+
+impl Schedule {
+    pub fn temperature(&self, i: usize, iterations: usize) -> f64 {
+        use Schedule::*;
+        match *self {
+            Constant(t) => t,
+            Linear { start, end } => {
+                let progress = i as f64 / iterations.saturating_sub(1).max(1) as f64;
+
+                start + (end - start) * progress.min(1.)
+            }
+            Exponential { start, decay } => start * decay.powf(i as f64),
+            CosineRestarts { max, min, period } => {
+                let phase = (i % period.max(1)) as f64 / period.max(1) as f64;
+
+                min + (max - min) * (1. + (std::f64::consts::PI * phase).cos()) / 2.
+            }
         }
     }
+}
 
-    (i, best_candidate, best_analysis)
+// For optimization rather than sampling: we only care about the best term found, so the
+// chain is cooled towards greedy ascent & restarted when it gets stuck.
+#[derive(Clone, Debug)]
+pub struct Annealing {
+    pub schedule: Schedule,
+    // Restart from a fresh term once the best score hasn't improved in this many iterations
+    pub patience: Option<usize>,
 }
 
-// Mutates a &Term. Also returns g(x|x') / g(x'|x) [where x' is the proposal]
-fn mutate<L: Language>(
+impl Default for Annealing {
+    fn default() -> Self {
+        Self {
+            schedule: Schedule::Exponential {
+                start: 10.,
+                decay: 0.9995,
+            },
+            patience: None,
+        }
+    }
+}
+
+// A single chain whose temperature follows the schedule. Restarts draw a term from the
+// prior of the same cost as `start` (or `start` itself, if there is no such term).
+pub fn anneal<F: FnMut(&Term) -> Option<f64>, L: Language>(
     lang: &L,
-    term: &Term,
+    start: &Term,
     ty: &Type,
-    cache: &mut SizeCache<L>,
-) -> Option<(Term, Analysis<L>, f64)> {
+    mut scorer: F,
+    iterations: usize,
+    options: Options<L>,
+    annealing: &Annealing,
+) -> (usize, Term, Analysis<L>, ChainReport, Stats) {
+    // Their state isn't saved, so a restarted run would silently start over
+    assert!(
+        options.checkpoint.is_none(),
+        "Annealing runs can't be checkpointed"
+    );
+
+    let mut i = 0;
+    let mut stats = Stats::new(&options.kernel);
+    let watch = options.limits.watch();
+
+    let mut chain = Chain {
+        candidate: start.clone(),
+        score: 0.,
+        report: ChainReport {
+            temperature: annealing.schedule.temperature(0, iterations),
+            ..Default::default()
+        },
+    };
+
+    let mut best = Best {
+        term: start.clone(),
+        score: 0.,
+        analysis: Analysis::Unique,
+    };
+
+    let Some(score) = scorer(start) else {
+        stats.stop = StopReason::Solved;
+        return (i, best.term, best.analysis, chain.report, stats);
+    };
+
+    chain.score = score;
+    stats.improved(i, score);
+    stats.visit(start);
+
     let ctxt = lang.context();
+    let prior = lang.prior();
+    let start_size = prior.cost(start);
 
-    use MutationTy::*;
-    match MutationTy::choose_replacement_kind() {
-        HVar => {
-            let term_meta = annotate_term(term, &ctxt, ty);
-
-            let (var_node, annotation, _) =
-                random_subnode(term, &term_meta, 1, 1);
-
-            let (_, replacement) = cache.sample(
-                lang,
-                annotation.decls,
-                &annotation.ty,
-                annotation.size,
-            );
-
-            let (new_var, var_analysis) = replacement.unwrap();
-
-            let (candidate, analysis) = replace_subnode(
-                lang,
-                term,
-                &term_meta,
-                var_node,
-                new_var,
-                var_analysis,
-            )?;
-
-            Some((candidate, analysis, 1.))
-        }
-        Small => {
-            let term_meta = annotate_term(term, &ctxt, ty);
-
-            let (replacement_node, annotation, _) =
-                random_subnode(term, &term_meta, 2, L::SMALL_SIZE);
-
-            let (_, replacement) = cache.sample(
-                lang,
-                annotation.decls,
-                &annotation.ty,
-                annotation.size,
-            );
-
-            let (new_term, new_analysis) = replacement.unwrap();
-
-            let (proposal, analysis) = replace_subnode(
-                lang,
-                term,
-                &term_meta,
-                replacement_node,
-                new_term,
-                new_analysis,
-            )?;
-
-            if !proposal.in_beta_normal_form() {
-                return None;
-            }
+    let mut cache = options.size_cache(lang);
+    let mut rng = options.rng();
+
+    let mut since_improvement = 0;
 
-            Some((proposal, analysis, 1.))
+    while i < iterations {
+        if let Some(reason) = watch.check() {
+            stats.stop = reason;
+            break;
         }
-        Large => {
-            use rand::distributions::Distribution;
-            use statrs::distribution::Binomial;
 
-            let term_meta = annotate_term(term, &ctxt, ty);
+        i += 1;
 
-            let (replacement_node, annotation, subnode_count) =
-                random_subnode(term, &term_meta, 2, L::LARGE_SIZE);
+        if let Some(freq) = options.print_freq {
+            if i % freq == 0 {
+                let progress = Progress {
+                    iteration: i,
+                    iterations,
+                    size: chain.candidate.size(),
+                    best_score: best.score,
+                    stats: &stats,
+                };
 
-            if subnode_count == 0 {
-                return None;
+                options.report(progress, |p| {
+                    println!(
+                        "Annealing progress: {}/{}. Size {}. T = {:.3}",
+                        p.iteration, p.iterations, p.size, chain.report.temperature
+                    )
+                });
             }
+        }
 
-            let ratio = annotation.size as f64 / L::LARGE_SIZE as f64;
-
-            let size_distr = Binomial::new(ratio, L::LARGE_SIZE as u64).ok()?;
-            let replacement_size: u64 =
-                size_distr.sample(&mut rand::thread_rng());
-            let replacement_size = replacement_size as usize;
-
-            let (new_count, replacement) = cache.sample(
-                lang,
-                annotation.decls.clone(),
-                &annotation.ty,
-                replacement_size,
-            );
-
-            let (replacement, replacement_analysis) = replacement?;
-
-            let (proposal, analysis) = replace_subnode(
-                lang,
-                term,
-                &term_meta,
-                replacement_node,
-                replacement,
-                replacement_analysis,
-            )?;
-
-            if !proposal.in_beta_normal_form() {
-                return None;
-            }
+        chain.report.temperature = annealing.schedule.temperature(i - 1, iterations);
+
+        let mut proposer = Proposer::new(lang, &ctxt, &prior, &mut rng, &mut cache);
+        let best_score = best.score;
+
+        if let Some((term, analysis)) = chain.step(
+            &options.kernel,
+            &mut proposer,
+            &mut scorer,
+            ty,
+            &mut best,
+            &mut stats,
+        ) {
+            stats.stop = StopReason::Solved;
+            return (i, term, analysis, chain.report, stats);
+        }
 
-            let old_count = cache.query_count(
-                lang,
-                annotation.decls,
-                &annotation.ty,
-                annotation.size,
-            );
+        stats.improved(i, best.score);
+        stats.step(chain.candidate.size());
 
-            // g1 = g(x' | x)
-            let g1 = g::<L>(
-                subnode_count,
-                replacement_size,
-                annotation.size,
-                new_count,
-            );
+        if best.score > best_score {
+            since_improvement = 0;
+        } else {
+            since_improvement += 1;
+        }
 
-            let prop_meta = annotate_term(&proposal, &ctxt, ty);
+        if Some(since_improvement) == annealing.patience {
+            let (_, fresh) = proposer.sample(vec![], ty, start_size);
+            let (fresh, analysis) = fresh.unwrap_or_else(|| (start.clone(), Analysis::Unique));
 
-            let (_, _, subnode_count) =
-                random_subnode(&proposal, &prop_meta, 2, L::LARGE_SIZE);
+            let Some(score) = scorer(&fresh) else {
+                stats.stop = StopReason::Solved;
+                return (i, fresh, analysis, chain.report, stats);
+            };
 
-            if subnode_count == 0 {
-                return None;
-            }
+            stats.improved(i, score);
+            stats.visit(&fresh);
 
-            //g2 = g(x | x')
-            let g2 = g::<L>(
-                subnode_count,
-                annotation.size,
-                replacement_size,
-                old_count,
-            );
+            chain.candidate = fresh;
+            chain.score = score;
+            chain.report.restarts += 1;
 
-            Some((proposal, analysis, g2 / g1))
+            since_improvement = 0;
         }
     }
+
+    (i, best.term, best.analysis, chain.report, stats)
 }
 
-// g(x2 | x1)
-fn g<L: Language>(
-    x1_subnode_count: usize,
-    delta_size: usize,
-    replaced_size: usize,
-    x2_num_replacement_terms: usize,
-) -> f64 {
-    use statrs::distribution::Binomial;
+// Restores the state saved by `checkpointing`, if any, & returns the iteration reached
+fn resume<L: Language>(
+    lang: &L,
+    ty: &Type,
+    checkpointing: &Checkpointing,
+    chains: &mut [Chain],
+    best: &mut Best<L>,
+    rng: &mut ChaCha8Rng,
+    cache: &mut SizeCache<L>,
+) -> usize {
+    let sizes = checkpointing.size_cache_path();
+
+    if checkpointing.size_cache {
+        cache.load(&sizes);
+    }
+
+    let checkpoint = match checkpointing.resume() {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => return 0,
+        Err(err) => panic!(
+            "Can't resume from {}: {}",
+            checkpointing.path.display(),
+            err
+        ),
+    };
+
+    assert_eq!(
+        checkpoint.chains.len(),
+        chains.len(),
+        "The checkpoint has a different number of chains"
+    );
+
+    // (`seeded` has already picked up the checkpoint's seed)
+    rng.set_word_pos(checkpoint.word_pos);
 
-    let prob_subnode_selected = 1. / x1_subnode_count as f64;
+    for (chain, (term, score, report)) in chains.iter_mut().zip(checkpoint.chains) {
+        chain.candidate = term;
+        chain.score = score;
+        chain.report = report;
+    }
 
-    let ratio = replaced_size as f64 / L::LARGE_SIZE as f64;
+    let (term, score) = checkpoint.best;
+    let meta = annotate_term(&term, &lang.context(), &lang.prior(), ty);
 
-    let size_distr = Binomial::new(ratio, L::LARGE_SIZE as u64).unwrap();
+    best.analysis = analyze_term(lang, &term, &meta);
+    best.score = score;
+    best.term = term;
 
-    let prob_size_selected = size_distr.pmf(delta_size as u64);
+    checkpoint.iteration
+}
 
-    let prob_replacement_generated = 1. / x2_num_replacement_terms as f64;
+fn save<L: Language>(
+    checkpointing: &Checkpointing,
+    checkpoint: &Checkpoint,
+    cache: &SizeCache<L>,
+) -> io::Result<()> {
+    if checkpointing.size_cache {
+        cache.save(&checkpointing.size_cache_path())?;
+    }
 
-    prob_subnode_selected * prob_size_selected * prob_replacement_generated
+    checkpoint.save(&checkpointing.path)
+}
+
+struct Chain {
+    candidate: Term,
+    score: f64,
+    report: ChainReport,
+}
+
+struct Best<L: Language> {
+    term: Term,
+    score: f64,
+    analysis: Analysis<L>,
+}
+
+impl Chain {
+    // Returns the proposal if the scorer accepts it as a solution
+    fn step<L: Language>(
+        &mut self,
+        kernel: &MutationKernel<L>,
+        proposer: &mut Proposer<'_, L>,
+        scorer: &mut impl FnMut(&Term) -> Option<f64>,
+        ty: &Type,
+        best: &mut Best<L>,
+        stats: &mut Stats,
+    ) -> Option<(Term, Analysis<L>)> {
+        let (kind, proposal) = kernel.propose_indexed(proposer, &self.candidate, ty)?;
+
+        // g_ratio = g(x|x') / g(x'|x)
+        let Some((proposal, analysis, g_ratio)) = proposal else {
+            stats.mutations[kind].failures += 1;
+            return None;
+        };
+
+        self.report.proposals += 1;
+        stats.mutations[kind].proposals += 1;
+
+        let Some(proposal_score) = scorer(&proposal) else {
+            return Some((proposal, analysis));
+        };
+
+        if proposal_score > best.score {
+            best.analysis = analysis;
+            best.score = proposal_score;
+            best.term = proposal.clone();
+        }
+
+        // The chain samples in proportion to score^(1 / temperature)
+        let score_ratio = (proposal_score / self.score).powf(1. / self.report.temperature);
+
+        let acceptance_prob = score_ratio * g_ratio;
+
+        if with_probability(proposer.rng, acceptance_prob) {
+            self.candidate = proposal;
+            self.score = proposal_score;
+            self.report.accepted += 1;
+
+            stats.mutations[kind].accepted += 1;
+            stats.visit(&self.candidate);
+        }
+
+        None
+    }
 }
 
 pub fn replace_subnode<L: Language>(
@@ -290,15 +819,6 @@ pub fn replace_subnode<L: Language>(
 
         use Term::*;
         match dest {
-            Ref(r) => helper(
-                counter,
-                lang,
-                &(**r).borrow(),
-                dest_meta,
-                node_id,
-                src,
-                src_analysis,
-            ),
             Lam(v, b) => {
                 let (body, body_anal) = helper(
                     counter,
@@ -315,7 +835,6 @@ pub fn replace_subnode<L: Language>(
                 Some((Lam(*v, body.into()), lang.slam(*v, body_anal, ty)))
             }
             App(l, r) => {
-                let l = &*(**l).borrow();
                 let (l, l_analysis) = helper(
                     counter,
                     lang,
@@ -326,7 +845,6 @@ pub fn replace_subnode<L: Language>(
                     src_analysis.clone(),
                 )?;
 
-                let r = &*(**r).borrow();
                 let (r, r_analysis) = helper(
                     counter,
                     lang,
@@ -354,41 +872,69 @@ pub fn replace_subnode<L: Language>(
     helper(&mut 0, lang, dest, dest_meta, node_id, src, src_analysis)
 }
 
+// The number of nodes `random_subnode` chooses between
+pub fn count_subnodes(
+    term: &Term,
+    meta: &Metadata,
+    selectable: impl Fn(&Term, &Annotation) -> bool,
+) -> usize {
+    let mut stack = vec![term];
+    let mut count = 0;
+
+    while let Some(next) = stack.pop() {
+        if selectable(next, &meta[&(next as *const Term)]) {
+            count += 1;
+        }
+
+        use Term::*;
+        match next {
+            Lam(_, b) => stack.push(b),
+            App(l, r) => {
+                stack.push(r);
+                stack.push(l);
+            }
+            _ => (),
+        }
+    }
+
+    count
+}
+
 // Reservoir sampling, again.
 // We return the index of the subnode (using pre-order numbering) & its size
-// Returns (node_id, annotation, small_node_count)
+// Returns (node_id, annotation, selectable_node_count)
 pub fn random_subnode(
+    rng: &mut impl Rng,
     term: &Term,
     meta: &Metadata,
-    min_size: usize,
-    max_size: usize,
+    selectable: impl Fn(&Term, &Annotation) -> bool,
 ) -> (usize, Annotation, usize) {
     let mut selected_id: usize = 0;
-    let mut stack = vec![(term.clone(), term as *const Term)];
+    let mut stack = vec![term];
     let mut counter = 1;
     let mut small_counter = 0;
 
     let ptr = term as *const Term;
     let mut annotation = meta.get(&ptr).unwrap();
 
-    while let Some((next, ptr)) = stack.pop() {
-        let size = next.size();
+    while let Some(next) = stack.pop() {
+        let ptr = next as *const Term;
+        let next_annotation = meta.get(&ptr).unwrap();
 
-        if (min_size..=max_size).contains(&size) {
+        if selectable(next, next_annotation) {
             small_counter += 1;
-            if with_probability(1. / small_counter as f64) {
+            if with_probability(rng, 1. / small_counter as f64) {
                 selected_id = counter;
-                annotation = meta.get(&ptr).unwrap();
+                annotation = next_annotation;
             }
         }
 
         use Term::*;
         match next {
-            Ref(r) => stack.push((r.borrow().clone(), r.as_ptr())),
-            Lam(_, b) => stack.push(((*b).clone(), b.as_ref() as *const Term)),
+            Lam(_, b) => stack.push(b),
             App(l, r) => {
-                stack.push((r.borrow().clone(), r.as_ptr()));
-                stack.push((l.borrow().clone(), l.as_ptr()));
+                stack.push(r);
+                stack.push(l);
             }
             _ => (),
         }
@@ -411,12 +957,11 @@ fn show_node_id(term: &Term, id: usize) {
 
         use Term::*;
         match term {
-            Ref(r) => helper(&r.borrow(), id, counter),
             Val(_) | Var(_) => (),
             Lam(_, term) => helper(term, id, counter),
             App(left, right) => {
-                helper(&left.borrow(), id, counter);
-                helper(&right.borrow(), id, counter);
+                helper(left, id, counter);
+                helper(right, id, counter);
             }
         }
     }
@@ -426,22 +971,17 @@ fn show_node_id(term: &Term, id: usize) {
 
 #[derive(Clone, Debug)]
 pub struct Annotation {
-    size: usize,
-    ty: Type,
-    decls: VarsVec, // Variables in scope
-}
-
-type Metadata = HashMap<*const Term, Annotation>;
-
-// Can fail if Term is not in beta-nf
-fn annotate_term(term: &Term, ctxt: &Context, ty: &Type) -> Metadata {
-    fn annotate(
-        term: &Term,
-        ctxt: &Context,
-        ty: Option<&Type>,
-        map: &mut Metadata,
-        decls: &VarsVec,
-    ) {
+    pub size: usize, // Cost under the language's prior
+    pub weight: f64, // See `Prior::weight`
+    pub ty: Type,
+    pub decls: VarsVec, // Variables in scope
+}
+
+pub type Metadata = HashMap<*const Term, Annotation>;
+
+// Panics if the term is ill-typed
+pub(super) fn annotate_term(term: &Term, ctxt: &Context, prior: &Prior, ty: &Type) -> Metadata {
+    fn annotate(term: &Term, prior: &Prior, typing: &mut Typing, map: &mut Metadata) {
         let ptr = term as *const Term;
 
         if map.contains_key(&ptr) {
@@ -449,203 +989,134 @@ fn annotate_term(term: &Term, ctxt: &Context, ty: &Type) -> Metadata {
         }
 
         use Term::*;
-        let annotation = match term {
-            Ref(r) => {
-                annotate(&r.borrow(), ctxt, ty, map, decls);
-
-                let ptr = r.as_ptr() as *const Term;
-
-                map.get(&ptr).unwrap().clone()
-            }
-            Val(_) => Annotation {
-                size: 1,
-                decls: decls.clone(),
-                ty: ty.unwrap().clone(),
-            },
-            Var(v) => {
-                if let Some((_, v_ty)) = decls.iter().find(|(s, _)| v == s) {
-                    Annotation {
-                        size: 1,
-                        ty: (**v_ty).clone(),
-                        decls: decls.clone(),
-                    }
-                } else if let Some(builtin) = ctxt.get(*v) {
-                    Annotation {
-                        size: 1,
-                        ty: (*builtin.ty).clone(),
-                        decls: decls.clone(),
-                    }
-                } else {
-                    panic!("Undeclared variable")
-                }
-            }
-            Lam(v, b) => {
-                let ty = ty.unwrap().clone();
-
-                let Type::Fun(arg, ret) = ty.clone() else {
-                    unimplemented!()
-                };
-
-                let decls = decls.clone();
-
-                let mut body_decls = decls.clone();
-                body_decls.push((*v, arg.clone()));
-
-                annotate(b, ctxt, Some(ret.as_ref()), map, &body_decls);
-
-                Annotation {
-                    size: term.size(),
-                    ty,
-                    decls,
-                }
-            }
+        match term {
+            Val(_) | Var(_) => (),
+            Lam(_, b) => annotate(b, prior, typing, map),
             App(l, r) => {
-                let f = l.as_ptr() as *const Term;
-
-                annotate(&l.borrow(), ctxt, None, map, decls);
-
-                let f_note = map.get(&f).unwrap().clone();
-
-                let Type::Fun(arg, ret) = f_note.ty else {
-                    unreachable!()
-                };
+                annotate(l, prior, typing, map);
+                annotate(r, prior, typing, map);
+            }
+        }
 
-                annotate(&r.borrow(), ctxt, Some(&*arg), map, decls);
+        let (ty, decls) = typing.remove(&ptr).unwrap();
 
-                Annotation {
-                    size: term.size(),
-                    ty: (*ret).clone(),
-                    decls: f_note.decls,
-                }
-            }
+        let annotation = Annotation {
+            size: prior.cost(term),
+            weight: prior.weight(term),
+            ty,
+            decls,
         };
 
         map.insert(ptr, annotation);
     }
 
+    let mut typing = match type_subterms(ctxt, term, ty) {
+        Ok(typing) => typing,
+        Err(err) => panic!("`{}` is ill-typed: {}", term, err),
+    };
+
     let mut map = Metadata::default();
-    annotate(term, ctxt, Some(ty), &mut map, &vec![]);
+    annotate(term, prior, &mut typing, &mut map);
     map
 }
 
-#[derive(Debug)]
-enum MutationTy {
-    HVar,
-    Small,
-    Large,
-}
-
-impl MutationTy {
-    pub fn choose_replacement_kind() -> Self {
-        let rand = random();
-
-        if rand < REPLACE_VAR {
-            Self::HVar
-        } else if rand < REPLACE_VAR + REPLACE_SMALL {
-            Self::Small
-        } else {
-            Self::Large
-        }
-    }
+pub(super) fn analyze_term<L: Language>(lang: &L, term: &Term, meta: &Metadata) -> Analysis<L> {
+    analyze_with(lang, term, &|t| meta[&(t as *const Term)].ty.clone())
 }
 
 type CtxtCache<L> = HashMap<(Type, usize), CacheEntry<L>>;
 
-struct SizeCache<L: Language> {
+// Terms are drawn in proportion to their prior probability
+pub(super) struct SizeCache<L: Language> {
     map: HashMap<VarsVec, CtxtCache<L>>,
+    prior: Prior,
+    // Of the language, which saved masses must match
+    fingerprint: u64,
+    // Saved here when dropped
+    persist: Option<PathBuf>,
+    // For masses which haven't been sampled from, if set
+    counter: Option<Counter<f64>>,
 }
 
 #[derive(Debug)]
 enum CacheEntry<L: Language> {
-    Explicit(Vec<(Term, Analysis<L>)>),
-    Count(usize),
+    Explicit(Vec<(Term, Analysis<L>, f64)>),
+    // Total weight of terms
+    Mass(f64),
 }
 
 impl<L: Language> SizeCache<L> {
     const MAX_IN_MEM: usize = 32;
 
+    pub fn new(lang: &L) -> Self {
+        Self {
+            map: Default::default(),
+            prior: lang.prior(),
+            fingerprint: context_fingerprint(lang),
+            persist: None,
+            counter: None,
+        }
+    }
+
+    // Also returns the probability that the term was chosen
     pub fn sample(
         &mut self,
+        rng: &mut impl Rng,
         lang: &L,
         mut decls: VarsVec,
         ty: &Type,
         size: usize,
-    ) -> (usize, Option<(Term, Analysis<L>)>) {
+    ) -> (f64, Option<(Term, Analysis<L>)>) {
         use CacheEntry::*;
 
         decls.sort();
         let query = (ty.clone(), size);
 
         let map = self.map.entry(decls.clone()).or_default();
-        if let Some(cache_entry) = map.get(&query) {
-            match cache_entry {
-                Count(0) => {
-                    return (0, None);
-                }
-                Explicit(explicit) => {
-                    let len = explicit.len();
-                    let id = (random() * len as f64) as usize;
-
-                    return (explicit.len(), Some(explicit[id].clone()));
-                }
-                _ => (),
-            }
+        match map.get(&query) {
+            Some(Mass(mass)) if *mass == 0. => return (0., None),
+            Some(Explicit(explicit)) => return choose(rng, explicit),
+            _ => (),
         }
 
-        let mut explicit = Vec::with_capacity(Self::MAX_IN_MEM);
-
-        let mut search = search(lang, decls.clone(), ty, size);
+        let prior = &self.prior;
+        let weigh = |(term, analysis): (Term, Analysis<L>)| {
+            let weight = prior.weight(&term);
+            (term, analysis, weight)
+        };
 
-        while explicit.len() < Self::MAX_IN_MEM {
-            if let Some(next) = search.next() {
-                explicit.push(next);
-            } else if !explicit.is_empty() {
-                let len = explicit.len();
-                let id = (random() * len as f64) as usize;
+        let mut search = search(lang, decls.clone(), ty, size).map(weigh);
 
-                map.entry(query)
-                    .or_insert_with(|| Explicit(explicit.clone()));
+        let explicit = search.by_ref().take(Self::MAX_IN_MEM).collect::<Vec<_>>();
 
-                return (len, Some(explicit.swap_remove(id)));
-            } else {
-                map.entry(query).or_insert(Count(0));
-                return (0, None);
-            }
+        if explicit.is_empty() {
+            map.insert(query, Mass(0.));
+            return (0., None);
         }
 
-        let (rest_count, selected) = reservoir_sample(search);
-
-        if selected.is_none() {
-            let len = explicit.len();
-            let id = (random() * len as f64) as usize;
+        let (rest_mass, selected) =
+            weighted_reservoir_sample(rng, search.map(|(t, a, w)| ((t, a, w), w)));
 
-            map.entry(query)
-                .or_insert_with(|| Explicit(explicit.clone()));
-
-            return (len, Some(explicit.swap_remove(id)));
-        }
+        let Some((term, analysis, weight)) = selected else {
+            let choice = choose(rng, &explicit);
+            map.insert(query, Explicit(explicit));
+            return choice;
+        };
 
-        let total_count = Self::MAX_IN_MEM + rest_count;
-        let prob = rest_count as f64 / total_count as f64;
+        let explicit_mass: f64 = explicit.iter().map(|(_, _, w)| w).sum();
+        let total_mass = explicit_mass + rest_mass;
 
-        map.entry(query).or_insert(Count(total_count));
+        map.insert(query, Mass(total_mass));
 
-        if with_probability(prob) {
-            let len = explicit.len();
-            let id = (random() * len as f64) as usize;
-            return (total_count, Some(explicit.swap_remove(id)));
+        if with_probability(rng, rest_mass / total_mass) {
+            return (weight / total_mass, Some((term, analysis)));
         }
 
-        (total_count, selected)
+        let (prob, choice) = choose(rng, &explicit);
+        (prob * explicit_mass / total_mass, choice)
     }
 
-    pub fn query_count(
-        &mut self,
-        lang: &L,
-        mut decls: VarsVec,
-        ty: &Type,
-        size: usize,
-    ) -> usize {
+    pub fn query_mass(&mut self, lang: &L, mut decls: VarsVec, ty: &Type, size: usize) -> f64 {
         use CacheEntry::*;
         let query = (ty.clone(), size);
 
@@ -655,23 +1126,170 @@ impl<L: Language> SizeCache<L> {
 
         if let Some(entry) = map.get(&query) {
             return match entry {
-                Count(count) => *count,
-                Explicit(v) => v.len(),
+                Mass(mass) => *mass,
+                Explicit(v) => v.iter().map(|(_, _, w)| w).sum(),
             };
         }
 
-        let count = search(lang, decls, ty, size).count();
+        let mass = match &mut self.counter {
+            Some(counter) => counter.count(lang, decls, ty, size),
+            None => search(lang, decls, ty, size)
+                .map(|(term, _)| self.prior.weight(&term))
+                .sum(),
+        };
 
-        map.insert(query, Count(count));
+        map.insert(query, Mass(mass));
 
-        count
+        mass
     }
 }
 
-impl<L: Language> Default for SizeCache<L> {
-    fn default() -> Self {
-        Self {
-            map: Default::default(),
+impl<L: Language> SizeCache<L> {
+    // Written to a temporary file first, like checkpoints. Other processes may be saving
+    // to the same path, so the temporary file is our own.
+    pub(super) fn save(&self, path: &Path) -> io::Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(format!(".{}.tmp", std::process::id()));
+
+        let mut out = io::BufWriter::new(std::fs::File::create(&tmp)?);
+        self.save_masses(&mut out)?;
+        out.flush()?;
+        drop(out);
+
+        std::fs::rename(tmp, path)
+    }
+
+    // After the fingerprint, one line per query: size, mass, type & the sorted declarations.
+    // Explicit samples are cheap to rebuild (& carry analyses), so only their total mass is kept.
+    pub(super) fn save_masses(&self, out: &mut impl Write) -> io::Result<()> {
+        use CacheEntry::*;
+
+        writeln!(out, "{:016x}", self.fingerprint)?;
+
+        for (decls, queries) in &self.map {
+            let has_uuid = |ty: &Type| ty.vars().iter().any(|v| matches!(v, Identifier::Uuid(_)));
+
+            // The parser can't read these back
+            if decls
+                .iter()
+                .any(|(v, ty)| matches!(v, Identifier::Uuid(_)) || has_uuid(ty))
+            {
+                continue;
+            }
+
+            for ((ty, size), entry) in queries {
+                if has_uuid(ty) {
+                    continue;
+                }
+
+                let mass: f64 = match entry {
+                    Mass(mass) => *mass,
+                    Explicit(v) => v.iter().map(|(_, _, w)| w).sum(),
+                };
+
+                write!(out, "{}\t{}\t{}", size, mass, ty)?;
+                for (v, ty) in decls {
+                    write!(out, "\t{}:{}", v, ty)?;
+                }
+                writeln!(out)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // The masses saved at `path`, if any. They can always be recounted, so a bad file is
+    // discarded (with a warning) rather than failing the run.
+    pub(super) fn load(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+
+        let loaded =
+            std::fs::File::open(path).and_then(|f| self.load_masses(io::BufReader::new(f)));
+
+        if let Err(err) = loaded {
+            eprintln!("Discarding size cache {}: {}", path.display(), err);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    // Nothing is added unless the whole file is valid
+    pub(super) fn load_masses(&mut self, input: impl BufRead) -> io::Result<()> {
+        let invalid = |line: &str| io::Error::new(io::ErrorKind::InvalidData, line.to_string());
+
+        let mut lines = input.lines();
+
+        let header = lines.next().transpose()?;
+        if header.and_then(|h| u64::from_str_radix(&h, 16).ok()) != Some(self.fingerprint) {
+            return Err(invalid("masses were counted for another language"));
+        }
+
+        let mut masses = vec![];
+
+        for line in lines {
+            let line = line?;
+            let mut fields = line.split('\t');
+
+            let (Some(size), Some(mass), Some(ty)) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid(&line));
+            };
+
+            let size = size.parse::<usize>().map_err(|_| invalid(&line))?;
+            let mass = mass.parse::<f64>().map_err(|_| invalid(&line))?;
+            let ty = parse_type(ty).map_err(|_| invalid(&line))?;
+
+            let decls = fields
+                .map(|decl| {
+                    let (v, ty) = decl.split_once(':')?;
+                    Some((Identifier::intern(v), Arc::new(parse_type(ty).ok()?)))
+                })
+                .collect::<Option<VarsVec>>()
+                .ok_or_else(|| invalid(&line))?;
+
+            masses.push((decls, ty, size, mass));
         }
+
+        for (decls, ty, size, mass) in masses {
+            self.map
+                .entry(decls)
+                .or_default()
+                .entry((ty, size))
+                .or_insert(CacheEntry::Mass(mass));
+        }
+
+        Ok(())
     }
 }
+
+impl<L: Language> Drop for SizeCache<L> {
+    fn drop(&mut self) {
+        let Some(path) = &self.persist else {
+            return;
+        };
+
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| self.save(path));
+
+        if let Err(err) = saved {
+            eprintln!("Failed to save size cache {}: {}", path.display(), err);
+        }
+    }
+}
+
+// Weighted choice from a nonempty list, & the probability of that choice
+fn choose<L: Language>(
+    rng: &mut impl Rng,
+    explicit: &[(Term, Analysis<L>, f64)],
+) -> (f64, Option<(Term, Analysis<L>)>) {
+    let weights = explicit.iter().map(|(_, _, w)| *w).collect::<Vec<_>>();
+    let id = weighted_index(rng, &weights);
+
+    let (term, analysis, weight) = &explicit[id];
+    let mass: f64 = weights.iter().sum();
+
+    (weight / mass, Some((term.clone(), analysis.clone())))
+}
@@ -1,8 +1,14 @@
+mod checkpoint;
 pub mod metro;
+pub mod genetic;
+mod mutation;
 mod synth;
 mod utils;
 
+pub use checkpoint::*;
+pub use genetic::*;
 pub use metro::*;
+pub use mutation::*;
 pub use synth::*;
 
 use super::*;
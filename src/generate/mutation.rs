@@ -0,0 +1,368 @@
+// Proposal distributions for `metropolis`.
+// A kernel mixes several kinds of mutation with fixed weights. Each kind reports its own
+// ratio g(x|x') / g(x'|x), & since the weights don't depend on the term, the mixture
+// satisfies detailed balance whenever each kind does.
+
+use super::*;
+
+use rand_chacha::ChaCha8Rng;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+pub trait Mutation<L: Language>: Send + Sync {
+    // A proposal x' for x = `term`, with g(x|x') / g(x'|x)
+    fn propose(
+        &self,
+        proposer: &mut Proposer<'_, L>,
+        term: &Term,
+        ty: &Type,
+    ) -> Option<(Term, Analysis<L>, f64)>;
+
+    // Shown in statistics
+    fn name(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name).to_string()
+    }
+}
+
+// x', with its analysis & g(x|x') / g(x'|x)
+pub type Proposal<L> = (Term, Analysis<L>, f64);
+
+pub struct MutationKernel<L: Language> {
+    kinds: Vec<(f64, Arc<dyn Mutation<L>>)>,
+}
+
+// Everything a mutation may draw on, shared across the iterations of a chain
+pub struct Proposer<'a, L: Language> {
+    pub lang: &'a L,
+    pub ctxt: &'a Context,
+    pub prior: &'a Prior,
+    pub rng: &'a mut ChaCha8Rng,
+    cache: &'a mut SizeCache<L>,
+}
+
+impl<L: Language> MutationKernel<L> {
+    pub fn new() -> Self {
+        Self { kinds: vec![] }
+    }
+
+    pub fn with(mut self, weight: f64, mutation: impl Mutation<L> + 'static) -> Self {
+        assert!(weight >= 0., "Mutation weights must be non-negative");
+
+        self.kinds.push((weight, Arc::new(mutation)));
+        self
+    }
+
+    pub fn propose(
+        &self,
+        proposer: &mut Proposer<'_, L>,
+        term: &Term,
+        ty: &Type,
+    ) -> Option<(Term, Analysis<L>, f64)> {
+        self.propose_indexed(proposer, term, ty)?.1
+    }
+
+    // Also returns which kind of mutation was chosen (None iff there are no kinds)
+    pub fn propose_indexed(
+        &self,
+        proposer: &mut Proposer<'_, L>,
+        term: &Term,
+        ty: &Type,
+    ) -> Option<(usize, Option<Proposal<L>>)> {
+        if self.kinds.is_empty() {
+            return None;
+        }
+
+        let weights = self.kinds.iter().map(|(w, _)| *w).collect::<Vec<_>>();
+        let kind = weighted_index(proposer.rng, &weights);
+
+        Some((kind, self.kinds[kind].1.propose(proposer, term, ty)))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.kinds.iter().map(|(_, m)| m.name()).collect()
+    }
+}
+
+// Mostly cheap, local changes. Large replacements are much more computationally expensive
+// & can erase a lot of progress, but also allow us to exit local minima (we must calculate
+// g(x'|x) & g(x|x'), involving a census of terms we don't even use).
+impl<L: Language> Default for MutationKernel<L> {
+    fn default() -> Self {
+        Self::new()
+            .with(0.5, ReplaceVar)
+            .with(
+                0.4,
+                ReplaceSmall {
+                    sizes: 1..=L::SMALL_SIZE,
+                },
+            )
+            .with(
+                0.1,
+                ReplaceLarge {
+                    max_size: L::LARGE_SIZE,
+                },
+            )
+    }
+}
+
+impl<L: Language> Clone for MutationKernel<L> {
+    fn clone(&self) -> Self {
+        Self {
+            kinds: self.kinds.clone(),
+        }
+    }
+}
+
+impl<'a, L: Language> Proposer<'a, L> {
+    pub(super) fn new(
+        lang: &'a L,
+        ctxt: &'a Context,
+        prior: &'a Prior,
+        rng: &'a mut ChaCha8Rng,
+        cache: &'a mut SizeCache<L>,
+    ) -> Self {
+        Self {
+            lang,
+            ctxt,
+            prior,
+            rng,
+            cache,
+        }
+    }
+
+    // Panics if the term is ill-typed
+    pub fn annotate(&self, term: &Term, ty: &Type) -> Metadata {
+        annotate_term(term, self.ctxt, self.prior, ty)
+    }
+
+    // A term of the given type & cost, drawn according to the prior, with its probability
+    pub fn sample(
+        &mut self,
+        decls: VarsVec,
+        ty: &Type,
+        size: usize,
+    ) -> (f64, Option<(Term, Analysis<L>)>) {
+        self.cache.sample(self.rng, self.lang, decls, ty, size)
+    }
+
+    // Total weight of the terms of the given type & cost
+    pub fn query_mass(&mut self, decls: VarsVec, ty: &Type, size: usize) -> f64 {
+        self.cache.query_mass(self.lang, decls, ty, size)
+    }
+
+    pub fn random_subnode(
+        &mut self,
+        term: &Term,
+        meta: &Metadata,
+        selectable: impl Fn(&Term, &Annotation) -> bool,
+    ) -> (usize, Annotation, usize) {
+        random_subnode(self.rng, term, meta, selectable)
+    }
+}
+
+// Replaces a variable with another
+#[derive(Clone, Copy, Debug)]
+pub struct ReplaceVar;
+
+// Replaces a small (non-variable) subterm with another of equal cost
+#[derive(Clone, Debug)]
+pub struct ReplaceSmall {
+    pub sizes: RangeInclusive<usize>,
+}
+
+// Replaces a subterm with another, potentially of different cost
+#[derive(Clone, Copy, Debug)]
+pub struct ReplaceLarge {
+    pub max_size: usize,
+}
+
+impl<L: Language> Mutation<L> for ReplaceVar {
+    fn propose(
+        &self,
+        proposer: &mut Proposer<'_, L>,
+        term: &Term,
+        ty: &Type,
+    ) -> Option<(Term, Analysis<L>, f64)> {
+        let term_meta = proposer.annotate(term, ty);
+
+        let (var_node, annotation, _) =
+            proposer.random_subnode(term, &term_meta, |t, _| t.size() == 1);
+
+        let (new_prob, replacement) =
+            proposer.sample(annotation.decls.clone(), &annotation.ty, annotation.size);
+
+        let (new_var, var_analysis) = replacement?;
+
+        // Terms of the same cost needn't be variables (under a non-uniform prior), but the
+        // reverse move only selects variables
+        if new_var.size() != 1 {
+            return None;
+        }
+
+        let (candidate, analysis) = replace_subnode(
+            proposer.lang,
+            term,
+            &term_meta,
+            var_node,
+            new_var,
+            var_analysis,
+        )?;
+
+        let old_mass = proposer.query_mass(annotation.decls, &annotation.ty, annotation.size);
+
+        // The number of variables is unchanged, so only the sampling probabilities differ
+        Some((candidate, analysis, annotation.weight / old_mass / new_prob))
+    }
+}
+
+impl<L: Language> Mutation<L> for ReplaceSmall {
+    fn propose(
+        &self,
+        proposer: &mut Proposer<'_, L>,
+        term: &Term,
+        ty: &Type,
+    ) -> Option<(Term, Analysis<L>, f64)> {
+        let is_small = |t: &Term, a: &Annotation| t.size() > 1 && self.sizes.contains(&a.size);
+
+        let term_meta = proposer.annotate(term, ty);
+
+        let (replacement_node, annotation, subnode_count) =
+            proposer.random_subnode(term, &term_meta, is_small);
+
+        if subnode_count == 0 {
+            return None;
+        }
+
+        let (new_prob, replacement) =
+            proposer.sample(annotation.decls.clone(), &annotation.ty, annotation.size);
+
+        let (new_term, new_analysis) = replacement?;
+
+        // The reverse move must be able to select the replacement
+        if new_term.size() == 1 {
+            return None;
+        }
+
+        let (proposal, analysis) = replace_subnode(
+            proposer.lang,
+            term,
+            &term_meta,
+            replacement_node,
+            new_term,
+            new_analysis,
+        )?;
+
+        if !proposal.in_beta_normal_form() {
+            return None;
+        }
+
+        let old_mass = proposer.query_mass(annotation.decls, &annotation.ty, annotation.size);
+
+        // g1 = g(x' | x)
+        let g1 = new_prob / subnode_count as f64;
+
+        // The replacement may have a different number of small subterms
+        let prop_meta = proposer.annotate(&proposal, ty);
+        let prop_subnode_count = count_subnodes(&proposal, &prop_meta, is_small);
+
+        // g2 = g(x | x')
+        let g2 = annotation.weight / old_mass / prop_subnode_count as f64;
+
+        Some((proposal, analysis, g2 / g1))
+    }
+}
+
+impl<L: Language> Mutation<L> for ReplaceLarge {
+    fn propose(
+        &self,
+        proposer: &mut Proposer<'_, L>,
+        term: &Term,
+        ty: &Type,
+    ) -> Option<(Term, Analysis<L>, f64)> {
+        use rand::distributions::Distribution;
+        use statrs::distribution::Binomial;
+
+        let is_large = |t: &Term, a: &Annotation| t.size() > 1 && a.size <= self.max_size;
+
+        let term_meta = proposer.annotate(term, ty);
+
+        let (replacement_node, annotation, subnode_count) =
+            proposer.random_subnode(term, &term_meta, is_large);
+
+        if subnode_count == 0 {
+            return None;
+        }
+
+        let ratio = annotation.size as f64 / self.max_size as f64;
+
+        let size_distr = Binomial::new(ratio, self.max_size as u64).ok()?;
+        let replacement_size: u64 = size_distr.sample(proposer.rng);
+        let replacement_size = replacement_size as usize;
+
+        let (new_prob, replacement) =
+            proposer.sample(annotation.decls.clone(), &annotation.ty, replacement_size);
+
+        let (replacement, replacement_analysis) = replacement?;
+
+        let (proposal, analysis) = replace_subnode(
+            proposer.lang,
+            term,
+            &term_meta,
+            replacement_node,
+            replacement,
+            replacement_analysis,
+        )?;
+
+        if !proposal.in_beta_normal_form() {
+            return None;
+        }
+
+        let old_mass = proposer.query_mass(annotation.decls, &annotation.ty, annotation.size);
+
+        // g1 = g(x' | x)
+        let g1 = self.g(subnode_count, replacement_size, annotation.size, new_prob);
+
+        let prop_meta = proposer.annotate(&proposal, ty);
+
+        let prop_subnode_count = count_subnodes(&proposal, &prop_meta, is_large);
+
+        if prop_subnode_count == 0 {
+            return None;
+        }
+
+        //g2 = g(x | x')
+        let g2 = self.g(
+            prop_subnode_count,
+            annotation.size,
+            replacement_size,
+            annotation.weight / old_mass,
+        );
+
+        Some((proposal, analysis, g2 / g1))
+    }
+}
+
+impl ReplaceLarge {
+    // g(x2 | x1)
+    fn g(
+        &self,
+        x1_subnode_count: usize,
+        delta_size: usize,
+        replaced_size: usize,
+        prob_replacement_generated: f64,
+    ) -> f64 {
+        use statrs::distribution::{Binomial, Discrete};
+
+        let prob_subnode_selected = 1. / x1_subnode_count as f64;
+
+        let ratio = replaced_size as f64 / self.max_size as f64;
+
+        let size_distr = Binomial::new(ratio, self.max_size as u64).unwrap();
+
+        let prob_size_selected = size_distr.pmf(delta_size as u64);
+
+        prob_subnode_selected * prob_size_selected * prob_replacement_generated
+    }
+}
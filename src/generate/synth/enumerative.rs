@@ -0,0 +1,156 @@
+use super::*;
+
+#[derive(Clone, Debug, Default)]
+pub struct EnumerationReport {
+    pub programs: usize, // Tried
+    pub size: usize,     // Reached
+}
+
+// Tries every program in order of size, until one meets the specification (& generalizes
+// to any held-out examples, so the first solution is also the smallest). Of the
+// settings, `iterations` bounds the number of programs tried & `budget` their evaluation;
+// of the options, only the limits & progress reports apply. If no program was tried (e.g.
+// none are as small as `max_size`, or the run was stopped first), the run fails with the
+// reason it stopped. Without a maximum size or limits, it never ends if the type is
+// uninhabited.
+pub struct Enumerative<L: Language> {
+    pub max_size: Option<usize>,
+    pub settings: SynthesisParameters,
+    pub options: Options<L>,
+}
+
+pub fn enumerative_synthesize<L: Language>(
+    lang: L,
+    spec: &impl Specification,
+    max_size: Option<usize>,
+    settings: SynthesisParameters,
+    options: Options<L>,
+) -> Result<MetropolisOutput<L>, StopReason> {
+    let strategy = Enumerative {
+        max_size,
+        settings,
+        options,
+    };
+
+    synthesize(&lang, spec, strategy)
+}
+
+impl<L: Language> Strategy<L> for Enumerative<L> {
+    type Output = Result<MetropolisOutput<L>, StopReason>;
+
+    fn run<S: Specification>(self, lang: &L, spec: &S) -> Self::Output {
+        let Enumerative {
+            max_size,
+            settings,
+            options,
+        } = self;
+
+        let ty = spec.ty();
+        let judge = Judge::new(lang, spec, &settings).unwrap_or_else(|err| panic!("{}", err));
+        let mut overfit = 0;
+
+        let mut report = EnumerationReport::default();
+        let mut stats = Stats::default();
+        let watch = options.limits.watch();
+
+        // The first program with the least training error, or the first solution
+        let mut best: Option<(Term, Analysis<L>, Verdict)> = None;
+
+        let start_time = std::time::Instant::now();
+
+        'sizes: for size in 1.. {
+            if max_size.is_some_and(|max| size > max) {
+                stats.stop = StopReason::Exhausted;
+                break;
+            }
+
+            // Sizes may have no programs at all
+            if let Some(reason) = watch.check() {
+                stats.stop = reason;
+                break;
+            }
+
+            report.size = size;
+
+            for (term, analysis) in search(lang, vec![], &ty, size) {
+                if let Some(reason) = watch.check() {
+                    stats.stop = reason;
+                    break 'sizes;
+                }
+
+                if report.programs == settings.iterations {
+                    stats.stop = StopReason::Iterations;
+                    break 'sizes;
+                }
+
+                report.programs += 1;
+
+                if let Some(freq) = options.print_freq {
+                    if report.programs % freq == 0 {
+                        let progress = Progress {
+                            iteration: report.programs,
+                            iterations: settings.iterations,
+                            size,
+                            best_score: stats.best_trace.last().map_or(0., |(_, s)| *s),
+                            stats: &stats,
+                        };
+
+                        options.report(progress, |p| {
+                            println!(
+                                "Enumeration progress: {}/{}. Size {}",
+                                p.iteration, p.iterations, p.size
+                            )
+                        });
+                    }
+                }
+
+                let verdict = judge.judge(&term);
+
+                stats.visit(&term);
+                stats.step(size);
+
+                // Not a solution, but no worse than any program fitting the training examples
+                if verdict.overfits() {
+                    overfit += 1;
+                }
+
+                let solved = verdict.generalizes;
+
+                if solved || best.as_ref().is_none_or(|(.., b)| verdict.error < b.error) {
+                    if !solved {
+                        stats.improved(report.programs, judge.score(&verdict, &term));
+                    }
+
+                    best = Some((term, analysis, verdict));
+                }
+
+                if solved {
+                    stats.stop = StopReason::Solved;
+                    break 'sizes;
+                }
+            }
+        }
+
+        let time = start_time.elapsed().as_secs_f64();
+
+        let Some((term, analysis, verdict)) = best else {
+            return Err(stats.stop);
+        };
+
+        Ok(MetropolisOutput {
+            score: judge.final_score(&verdict, &term),
+            validation: judge.report(&term, overfit),
+            term,
+            iterations: report.programs,
+            seed: 0,
+            time,
+            num_correct: verdict.num_correct,
+            error: verdict.error,
+            analysis,
+            chains: vec![],
+            genetic: None,
+            enumeration: Some(report),
+            stats,
+        })
+    }
+}
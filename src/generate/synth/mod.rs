@@ -1,30 +1,58 @@
+pub mod enumerative;
 pub mod iterative;
 pub mod k_rec;
 pub mod pure_iterative;
 pub mod simple_map;
+pub mod spec;
+pub mod validation;
 
+pub use enumerative::*;
 pub use iterative::*;
 pub use k_rec::*;
 pub use pure_iterative::*;
 pub use simple_map::*;
+pub use spec::*;
+pub use validation::*;
 
 use super::*;
 
+use rustc_hash::{FxHashSet as HashSet, FxHasher};
 use statrs::distribution::{Continuous, Normal};
+use std::hash::{Hash, Hasher};
 
 pub struct SynthesisParameters {
     pub bias: SizeBias,
     pub score_factor: f64,
     pub iterations: usize,
+    pub budget: Budget, // Per-example evaluation limits
+    // Partial credit for incorrect examples (`score_factor` should suit the scale of its errors)
+    pub metric: ErrorMetric,
+    pub tempering: Tempering,
+    // Optimize with annealing instead of sampling (ignores `tempering`)
+    pub annealing: Option<Annealing>,
+    // Evolve a population instead (ignores `tempering` & `annealing`)
+    pub genetic: Option<Genetic>,
+    // Hold out examples, which solutions must also get right (synthesizers panic if that
+    // leaves none to train on)
+    pub validation: Option<Validation>,
 }
 
 pub struct MetropolisOutput<L: Language> {
     pub term: Term,
     pub iterations: usize,
+    // Replays the run, given the same arguments
+    pub seed: u64,
     pub time: f64,
+    // Of the training examples only, if some were held out
     pub num_correct: usize,
+    pub error: f64, // Total, under the metric of the parameters
     pub score: Option<f64>,
     pub analysis: Analysis<L>,
+    pub chains: Vec<ChainReport>,
+    pub genetic: Option<GeneticReport>,
+    pub enumeration: Option<EnumerationReport>,
+    pub validation: Option<ValidationReport>,
+    pub stats: Stats,
 }
 
 impl Default for SynthesisParameters {
@@ -33,12 +61,213 @@ impl Default for SynthesisParameters {
             bias: SizeBias::Unbiased,
             score_factor: 0.5,
             iterations: 50_000,
+            budget: Budget::default(),
+            metric: ErrorMetric::Exact,
+            tempering: Tempering::default(),
+            annealing: None,
+            genetic: None,
+            validation: None,
         }
     }
 }
 
+// How to search for a program meeting a specification
+pub trait Strategy<L: Language> {
+    // `MetropolisOutput`, unless the strategy may end without a program
+    type Output;
+
+    fn run<S: Specification>(self, lang: &L, spec: &S) -> Self::Output;
+}
+
+pub fn synthesize<L: Language, S: Specification, T: Strategy<L>>(
+    lang: &L,
+    spec: &S,
+    strategy: T,
+) -> T::Output {
+    strategy.run(lang, spec)
+}
+
+// Metropolis sampling (or annealing, or GP, as the parameters ask), scoring candidates by
+// exp(score_factor * (maximum total error - total error)), biased by size. With the
+// `Exact` metric, that's exp(score_factor * number of correct examples). Held-out
+// examples aren't scored, but the run only stops for a program passing them too.
+pub struct Stochastic<L: Language> {
+    // The smallest term of the right type, if unset
+    pub start: Option<Term>,
+    pub settings: SynthesisParameters,
+    pub options: Options<L>,
+}
+
+impl<L: Language> Strategy<L> for Stochastic<L> {
+    type Output = MetropolisOutput<L>;
+
+    fn run<S: Specification>(self, lang: &L, spec: &S) -> MetropolisOutput<L> {
+        let Stochastic {
+            start,
+            settings,
+            options,
+        } = self;
+
+        let ty = spec.ty();
+
+        let start = start.unwrap_or_else(|| smallest_term(lang, &ty).0);
+
+        let judge = Judge::new(lang, spec, &settings).unwrap_or_else(|err| panic!("{}", err));
+
+        // Chains revisit terms, so overfit ones are told apart (as in `Stats::visit`)
+        let mut overfit = HashSet::default();
+        // The smallest solution so far, when shortening
+        let mut shortest: Option<Term> = None;
+
+        let scorer = |term: &Term| {
+            let verdict = judge.judge(term);
+
+            if verdict.overfits() {
+                let mut hasher = FxHasher::default();
+                term.to_string().hash(&mut hasher);
+                overfit.insert(hasher.finish());
+            }
+
+            if verdict.generalizes {
+                if !judge.shorten() {
+                    return None;
+                }
+
+                if shortest.as_ref().is_none_or(|s| term.size() < s.size()) {
+                    shortest = Some(term.clone());
+                }
+            }
+
+            Some(judge.score(&verdict, term))
+        };
+
+        let options = options.seeded();
+        let seed = options.seed.unwrap();
+
+        let start_time = std::time::Instant::now();
+        let Run {
+            iterations,
+            mut term,
+            mut analysis,
+            chains,
+            genetic,
+            mut stats,
+        } = run_chains(lang, &start, &ty, scorer, &settings, options);
+        let end_time = std::time::Instant::now();
+
+        if let Some(shortest) = shortest {
+            let meta = annotate_term(&shortest, &lang.context(), &lang.prior(), &ty);
+
+            analysis = analyze_term(lang, &shortest, &meta);
+            term = shortest;
+            stats.stop = StopReason::Solved;
+        }
+
+        let verdict = judge.judge(&term);
+
+        MetropolisOutput {
+            iterations,
+            seed,
+            time: end_time.duration_since(start_time).as_secs_f64(),
+            num_correct: verdict.num_correct,
+            error: verdict.error,
+            score: judge.final_score(&verdict, &term),
+            validation: judge.report(&term, overfit.len()),
+            term,
+            analysis,
+            chains,
+            genetic,
+            enumeration: None,
+            stats,
+        }
+    }
+}
+
+// The first term the enumerator finds. Never returns if the type is uninhabited.
+fn smallest_term<L: Language>(lang: &L, ty: &Type) -> (Term, Analysis<L>) {
+    (1..)
+        .flat_map(|size| search(lang, vec![], ty, size))
+        .next()
+        .unwrap()
+}
+
+// Everything a synthesizer reports, besides how the result fares on the examples
+struct Run<L: Language> {
+    iterations: usize,
+    term: Term,
+    analysis: Analysis<L>,
+    chains: Vec<ChainReport>,
+    genetic: Option<GeneticReport>,
+    stats: Stats,
+}
+
+// Runs the search the parameters ask for: tempered sampling, annealing or GP
+fn run_chains<F: FnMut(&Term) -> Option<f64>, L: Language>(
+    lang: &L,
+    start: &Term,
+    ty: &Type,
+    scorer: F,
+    settings: &SynthesisParameters,
+    options: Options<L>,
+) -> Run<L> {
+    if let Some(params) = &settings.genetic {
+        let (iterations, term, analysis, report, stats) = genetic(
+            lang,
+            start,
+            ty,
+            scorer,
+            settings.iterations,
+            options,
+            params,
+        );
+
+        return Run {
+            iterations,
+            term,
+            analysis,
+            chains: vec![],
+            genetic: Some(report),
+            stats,
+        };
+    }
+
+    let (iterations, term, analysis, chains, stats) = match &settings.annealing {
+        Some(annealing) => {
+            let (i, term, analysis, report, stats) = anneal(
+                lang,
+                start,
+                ty,
+                scorer,
+                settings.iterations,
+                options,
+                annealing,
+            );
+
+            (i, term, analysis, vec![report], stats)
+        }
+        None => tempered_metropolis(
+            lang,
+            start,
+            ty,
+            scorer,
+            settings.iterations,
+            options,
+            &settings.tempering,
+        ),
+    };
+
+    Run {
+        iterations,
+        term,
+        analysis,
+        chains,
+        genetic: None,
+        stats,
+    }
+}
+
 // Used to bias programs towards reasonable sizes / prevent runaway term sizes
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum SizeBias {
     Unbiased,
     LinearBeyond { cutoff: usize, c: f64 },
@@ -81,19 +310,71 @@ impl<L: Language> MetropolisOutput<L> {
         let MetropolisOutput {
             term,
             iterations,
+            seed,
             time,
             num_correct,
+            error,
             score,
             analysis,
+            chains,
+            genetic,
+            enumeration,
+            validation,
+            stats,
         } = self;
 
         println!("Best Found: {}", &term);
         println!("Semantics:  {}", analysis);
 
-        println!("Score: {:?} (or {:?} correct)", score, num_correct,);
+        println!(
+            "Score: {:?} (or {:?} correct, with error {})",
+            score, num_correct, error
+        );
 
-        println!("Iterations: {}", iterations);
+        println!("Iterations: {} ({})", iterations, stats.stop);
+        println!("Seed: {}", seed);
         println!("Time (s): {}", time);
         println!("Time (s/iter): {}", time / *iterations as f64);
+
+        for chain in chains {
+            println!(
+                "Chain at T = {:.2}: acceptance {:.3}, swaps {:.3}, restarts {}",
+                chain.temperature,
+                chain.acceptance_rate(),
+                chain.swap_rate(),
+                chain.restarts
+            );
+        }
+
+        if let Some(report) = genetic {
+            println!(
+                "Generations: {} ({} crossovers, {} mutations, {} failures)",
+                report.generations, report.crossovers, report.mutations, report.failures
+            );
+        }
+
+        if let Some(report) = enumeration {
+            println!(
+                "Enumerated {} programs, up to size {}",
+                report.programs, report.size
+            );
+        }
+
+        if let Some(report) = validation {
+            println!(
+                "Validation: {}/{} held-out examples correct ({} overfit programs rejected)",
+                report.correct, report.held_out, report.overfit
+            );
+        }
+
+        for kind in &stats.mutations {
+            println!(
+                "{}: {} proposed, {} accepted, {} failed",
+                kind.name, kind.proposals, kind.accepted, kind.failures
+            );
+        }
+
+        println!("Mean size: {:.2}", stats.mean_size());
+        println!("Distinct terms: {}", stats.distinct_terms());
     }
 }
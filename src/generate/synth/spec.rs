@@ -0,0 +1,239 @@
+use super::*;
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+// A problem to solve by example: what programs the candidate is run in & what they
+// should evaluate to. Candidates are scored by how far off they are (see `ErrorMetric`).
+pub trait Specification {
+    // Of the candidates
+    fn ty(&self) -> Type;
+
+    fn num_examples(&self) -> usize;
+
+    // The program running `term` on the `i`th example
+    fn program(&self, term: &Term, i: usize) -> Term;
+
+    fn expected(&self, i: usize) -> &Value;
+
+    // None if evaluation failed, or didn't produce a value
+    fn output(&self, ctxt: &Context, term: &Term, i: usize, budget: Budget) -> Option<Value> {
+        ctxt.evaluate_with(&self.program(term, i), budget)
+            .ok()
+            .and_then(|evaled| evaled.leaf_val())
+    }
+
+    // Evaluation failures count as incorrect answers
+    fn passes(&self, ctxt: &Context, term: &Term, i: usize, budget: Budget) -> bool {
+        let output = self.output(ctxt, term, i, budget);
+
+        output.is_some_and(|output| self.expected(i).is_eq(&output))
+    }
+
+    fn num_correct(&self, ctxt: &Context, term: &Term, budget: Budget) -> usize {
+        (0..self.num_examples())
+            .filter(|&i| self.passes(ctxt, term, i, budget))
+            .count()
+    }
+
+    // The number of correct examples & the total error over all of them
+    fn errors(
+        &self,
+        ctxt: &Context,
+        term: &Term,
+        budget: Budget,
+        metric: &ErrorMetric,
+    ) -> (usize, f64) {
+        let mut num_correct = 0;
+        let mut total = 0.;
+
+        for i in 0..self.num_examples() {
+            let output = self.output(ctxt, term, i, budget);
+            let expected = self.expected(i);
+
+            if output.as_ref().is_some_and(|output| expected.is_eq(output)) {
+                num_correct += 1;
+            }
+
+            total += metric.error(output.as_ref(), expected);
+        }
+
+        (num_correct, total)
+    }
+}
+
+// How far an output is from the expected value. Every metric has a maximum error, which is
+// also the error of failing to produce an output (or one of the wrong type).
+#[derive(Clone, Default)]
+pub enum ErrorMetric {
+    // 0 if correct & 1 otherwise, so the total is the number of incorrect examples
+    // (e.g. the Hamming distance, for predicates)
+    #[default]
+    Exact,
+    // |output - expected|, for i32 & u32 outputs
+    Absolute {
+        max: f64,
+    },
+    // ln(1 + |output - expected|), for i32 & u32 outputs
+    Log {
+        max: f64,
+    },
+    Custom {
+        error: ErrorFn,
+        max: f64,
+    },
+}
+
+// Of the output & the expected value
+pub type ErrorFn = Arc<dyn Fn(&Value, &Value) -> f64 + Send + Sync>;
+
+impl ErrorMetric {
+    pub fn max(&self) -> f64 {
+        use ErrorMetric::*;
+        match self {
+            Exact => 1.,
+            Absolute { max } | Log { max } | Custom { max, .. } => *max,
+        }
+    }
+
+    pub fn error(&self, output: Option<&Value>, expected: &Value) -> f64 {
+        use ErrorMetric::*;
+
+        let Some(output) = output else {
+            return self.max();
+        };
+
+        let distance = || Some((as_number(output)? - as_number(expected)?).abs());
+
+        let error = match self {
+            Exact if expected.is_eq(output) => Some(0.),
+            Exact => Some(1.),
+            Absolute { .. } => distance(),
+            Log { .. } => distance().map(f64::ln_1p),
+            Custom { error, .. } => Some(error(output, expected)),
+        };
+
+        error.map_or(self.max(), |error| error.min(self.max()))
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    let any = value.as_any();
+
+    any.downcast_ref::<i32>()
+        .map(|n| *n as f64)
+        .or_else(|| any.downcast_ref::<u32>().map(|n| *n as f64))
+}
+
+// Candidates are applied to the arguments of each example. A new shape of problem
+// only needs a way to lay out its examples as arguments, like the constructors below.
+#[derive(Clone, Debug)]
+pub struct Examples {
+    pub ty: Type,
+    pub examples: Vec<(Vec<Term>, Value)>,
+}
+
+impl Examples {
+    pub fn new(ty: Type) -> Self {
+        Self {
+            ty,
+            examples: vec![],
+        }
+    }
+
+    pub fn with(mut self, args: Vec<Term>, output: impl TermValue) -> Self {
+        self.examples.push((args, Arc::new(output)));
+        self
+    }
+
+    // f(i) = o
+    pub fn map<I, O>(ty: Type, examples: impl Iterator<Item = (I, O)>) -> Self
+    where
+        I: TermValue,
+        O: TermValue,
+    {
+        examples.fold(Self::new(ty), |spec, (i, o)| {
+            spec.with(vec![Term::val(i)], o)
+        })
+    }
+
+    // f(previous output, i) = o, where the first previous output is `seed`
+    pub fn iterative<I, O>(ty: Type, seed: O, examples: impl Iterator<Item = (I, O)>) -> Self
+    where
+        I: TermValue,
+        O: TermValue,
+    {
+        let mut yielded = Term::val(seed);
+        let mut spec = Self::new(ty);
+
+        for (i, o) in examples {
+            let o: Value = Arc::new(o);
+
+            spec.examples.push((vec![yielded, Term::val(i)], o.clone()));
+            yielded = Term::Val(o);
+        }
+
+        spec
+    }
+
+    // f(previous output) = o, where the first previous output is `seed`
+    pub fn pure_iterative<O: TermValue>(
+        ty: Type,
+        seed: O,
+        examples: impl Iterator<Item = O>,
+    ) -> Self {
+        let mut yielded = Term::val(seed);
+        let mut spec = Self::new(ty);
+
+        for o in examples {
+            let o: Value = Arc::new(o);
+
+            spec.examples.push((vec![yielded], o.clone()));
+            yielded = Term::Val(o);
+        }
+
+        spec
+    }
+
+    // f(the previous k outputs) = o, where the first k outputs are given
+    pub fn k_rec<O: TermValue>(ty: Type, k: usize, examples: impl Iterator<Item = O>) -> Self {
+        let mut examples = examples.map(|o| Arc::new(o) as Value);
+        let mut prevs = examples
+            .by_ref()
+            .take(k)
+            .map(Term::Val)
+            .collect::<VecDeque<_>>();
+        let mut spec = Self::new(ty);
+
+        for o in examples {
+            let args = prevs.iter().cloned().collect();
+
+            spec.examples.push((args, o.clone()));
+
+            prevs.pop_front();
+            prevs.push_back(Term::Val(o));
+        }
+
+        spec
+    }
+}
+
+impl Specification for Examples {
+    fn ty(&self) -> Type {
+        self.ty.clone()
+    }
+
+    fn num_examples(&self) -> usize {
+        self.examples.len()
+    }
+
+    fn program(&self, term: &Term, i: usize) -> Term {
+        self.examples[i].0.iter().fold(term.clone(), |f, arg| {
+            Term::App(f.into(), arg.clone().into())
+        })
+    }
+
+    fn expected(&self, i: usize) -> &Value {
+        &self.examples[i].1
+    }
+}
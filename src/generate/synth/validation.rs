@@ -0,0 +1,187 @@
+// Holding out examples, so that programs which merely fit the others aren't taken for
+// solutions. Candidates are scored on the training examples only, but a run is only
+// solved by a program which gets the held-out ones right too.
+
+use super::*;
+
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+#[derive(Clone, Debug)]
+pub struct Validation {
+    // Examples held out from the end
+    pub held_out: usize,
+    // Keep going after a solution (until out of iterations), for a smaller one
+    pub shorten: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    pub held_out: usize,
+    pub correct: usize, // Of those held out
+    // Distinct programs which got every training example right, but not the held-out ones
+    pub overfit: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    NoTrainingExamples { held_out: usize, examples: usize },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ValidationError::*;
+        match self {
+            NoTrainingExamples { held_out, examples } => write!(
+                f,
+                "holding out {} of {} examples leaves none to train on",
+                held_out, examples
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+// Some of the examples of a specification
+pub struct Slice<'a, S: Specification> {
+    pub spec: &'a S,
+    pub examples: Range<usize>,
+}
+
+impl<S: Specification> Specification for Slice<'_, S> {
+    fn ty(&self) -> Type {
+        self.spec.ty()
+    }
+
+    fn num_examples(&self) -> usize {
+        self.examples.len()
+    }
+
+    fn program(&self, term: &Term, i: usize) -> Term {
+        self.spec.program(term, self.examples.start + i)
+    }
+
+    fn expected(&self, i: usize) -> &Value {
+        self.spec.expected(self.examples.start + i)
+    }
+}
+
+// The training & held-out examples. Holding out none is fine even without examples.
+pub fn split<S: Specification>(
+    spec: &S,
+    held_out: usize,
+) -> Result<(Slice<'_, S>, Slice<'_, S>), ValidationError> {
+    let n = spec.num_examples();
+
+    if held_out > 0 && held_out >= n {
+        return Err(ValidationError::NoTrainingExamples {
+            held_out,
+            examples: n,
+        });
+    }
+
+    let train = Slice {
+        spec,
+        examples: 0..n - held_out,
+    };
+
+    let valid = Slice {
+        spec,
+        examples: n - held_out..n,
+    };
+
+    Ok((train, valid))
+}
+
+// How a candidate fares on the training examples, & whether it generalizes
+pub(super) struct Verdict {
+    pub num_correct: usize,
+    pub error: f64,
+    pub fits: bool,
+    pub generalizes: bool,
+}
+
+// Scores candidates for the strategies
+pub(super) struct Judge<'a, S: Specification> {
+    train: Slice<'a, S>,
+    valid: Slice<'a, S>,
+    ctxt: Context,
+    settings: &'a SynthesisParameters,
+}
+
+impl Verdict {
+    pub fn overfits(&self) -> bool {
+        self.fits && !self.generalizes
+    }
+}
+
+impl<'a, S: Specification> Judge<'a, S> {
+    pub fn new<L: Language>(
+        lang: &L,
+        spec: &'a S,
+        settings: &'a SynthesisParameters,
+    ) -> Result<Self, ValidationError> {
+        let held_out = settings.validation.as_ref().map_or(0, |v| v.held_out);
+        let (train, valid) = split(spec, held_out)?;
+
+        Ok(Self {
+            train,
+            valid,
+            ctxt: lang.context(),
+            settings,
+        })
+    }
+
+    pub fn judge(&self, term: &Term) -> Verdict {
+        let settings = self.settings;
+
+        let (num_correct, error) =
+            self.train
+                .errors(&self.ctxt, term, settings.budget, &settings.metric);
+
+        let fits = num_correct == self.train.num_examples();
+
+        // Held-out examples are only checked once they could matter
+        let generalizes = fits
+            && self.valid.num_correct(&self.ctxt, term, settings.budget)
+                == self.valid.num_examples();
+
+        Verdict {
+            num_correct,
+            error,
+            fits,
+            generalizes,
+        }
+    }
+
+    // exp(score_factor * (maximum total error - total error)), biased by size
+    pub fn score(&self, verdict: &Verdict, term: &Term) -> f64 {
+        let settings = self.settings;
+        let max = self.train.num_examples() as f64 * settings.metric.max();
+
+        let prob_score = (settings.score_factor * (max - verdict.error)).exp();
+        settings.bias.apply(prob_score, term.size())
+    }
+
+    // None once the run is solved
+    pub fn final_score(&self, verdict: &Verdict, term: &Term) -> Option<f64> {
+        (!verdict.generalizes).then(|| self.score(verdict, term))
+    }
+
+    pub fn report(&self, term: &Term, overfit: usize) -> Option<ValidationReport> {
+        self.settings.validation.as_ref()?;
+
+        Some(ValidationReport {
+            held_out: self.valid.num_examples(),
+            correct: self
+                .valid
+                .num_correct(&self.ctxt, term, self.settings.budget),
+            overfit,
+        })
+    }
+
+    pub fn shorten(&self) -> bool {
+        self.settings.validation.as_ref().is_some_and(|v| v.shorten)
+    }
+}
@@ -1,31 +1,44 @@
 use rand::Rng;
 
-pub fn with_probability(p: f64) -> bool {
-    random() < p
+pub fn with_probability(rng: &mut impl Rng, p: f64) -> bool {
+    random(rng) < p
 }
 
-pub fn random() -> f64 {
-    rand::random::<f64>()
+pub fn random(rng: &mut impl Rng) -> f64 {
+    rng.gen::<f64>()
 }
 
-// Select one random element from iterator (using reservoir sampling)
-// Also returns # of elements. Needed for Metropolis-Hastings
+// Select one random element from iterator, with probability proportional to its weight
+// (using reservoir sampling). Also returns the total weight, needed for Metropolis-Hastings.
 // Returns None iff iter is empty.
-pub fn reservoir_sample<T>(
-    mut iter: impl Iterator<Item = T>,
-) -> (usize, Option<T>) {
-    let mut res = iter.next();
-    let mut count = 0;
-    let mut rng = rand::thread_rng();
-
-    for item in iter {
-        count += 1;
-        let r = rng.gen_range(0..=count);
-
-        if r == 0 {
+pub fn weighted_reservoir_sample<T>(
+    rng: &mut impl Rng,
+    iter: impl Iterator<Item = (T, f64)>,
+) -> (f64, Option<T>) {
+    let mut res = None;
+    let mut mass = 0.;
+
+    for (item, weight) in iter {
+        mass += weight;
+
+        if with_probability(rng, weight / mass) {
             res = Some(item);
         }
     }
 
-    (count, res)
+    (mass, res)
+}
+
+// Index chosen with probability proportional to its weight
+pub fn weighted_index(rng: &mut impl Rng, weights: &[f64]) -> usize {
+    let mut target = random(rng) * weights.iter().sum::<f64>();
+
+    for (i, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return i;
+        }
+        target -= weight;
+    }
+
+    weights.len() - 1
 }
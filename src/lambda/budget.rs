@@ -0,0 +1,169 @@
+// Resource limits for evaluation.
+// The active meter is thread-local, so that evaluations started from within
+// builtins (e.g. `exists`, `sigma`) draw from the same budget as the outermost one.
+
+use super::*;
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Display};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub steps: usize,         // Beta-reductions
+    pub builtin_calls: usize, // Calls to builtin functions
+    pub depth: usize,         // Recursion depth of the evaluator
+}
+
+impl Budget {
+    pub const UNLIMITED: Self = Self {
+        steps: usize::MAX,
+        builtin_calls: usize::MAX,
+        depth: usize::MAX,
+    };
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            steps: 1_000_000,
+            builtin_calls: 1_000_000,
+            depth: 4096,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resource {
+    Steps,
+    BuiltinCalls,
+    Depth,
+}
+
+#[derive(Clone, Debug)]
+pub enum EvalError {
+    OutOfFuel(Resource),
+    // Evaluation cannot proceed, but the result is not a value, abstraction or partial application
+    Stuck(Term),
+    // `Term::extract` found a value of the wrong type
+    TypeMismatch { expected: &'static str, found: Term },
+    BuiltinFailed(Identifier),
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use EvalError::*;
+        match self {
+            OutOfFuel(res) => write!(f, "out of fuel ({:?})", res),
+            Stuck(term) => write!(f, "stuck at `{}`", term),
+            TypeMismatch { expected, found } => {
+                write!(f, "expected value of type `{}`, found `{}`", expected, found)
+            }
+            BuiltinFailed(ident) => write!(f, "builtin `{}` failed", ident),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+pub(super) struct Meter {
+    budget: Budget,
+    spent: Budget,
+    exhausted: Option<Resource>,
+}
+
+thread_local! {
+    static METER: RefCell<Option<Meter>> = const { RefCell::new(None) };
+    static STEPS_SPENT: Cell<usize> = const { Cell::new(0) };
+}
+
+// Beta-reductions & builtin calls made by (metered) evaluations on this thread so far.
+// Differences between calls give the cost of whatever ran in between.
+pub fn evaluation_steps() -> usize {
+    STEPS_SPENT.get()
+}
+
+// Uninstalls the meter even if evaluation panics.
+struct Installed;
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        if let Some(meter) = METER.with_borrow_mut(Option::take) {
+            let spent = meter.spent.steps + meter.spent.builtin_calls;
+            STEPS_SPENT.set(STEPS_SPENT.get() + spent);
+        }
+    }
+}
+
+// Pops a level of recursion when dropped.
+pub(super) struct DepthGuard;
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        METER.with_borrow_mut(|meter| {
+            if let Some(meter) = meter {
+                meter.spent.depth -= 1;
+            }
+        });
+    }
+}
+
+impl Meter {
+    // Runs `f` under `budget`, unless we are already metered, in which case
+    // the current budget is shared.
+    pub fn run<T>(budget: Budget, f: impl FnOnce() -> T) -> T {
+        let nested = METER.with_borrow(Option::is_some);
+
+        if nested {
+            return f();
+        }
+
+        METER.with_borrow_mut(|meter| {
+            *meter = Some(Meter {
+                budget,
+                spent: Budget {
+                    steps: 0,
+                    builtin_calls: 0,
+                    depth: 0,
+                },
+                exhausted: None,
+            })
+        });
+
+        let _installed = Installed;
+        f()
+    }
+
+    pub fn charge(res: Resource) -> Result<(), EvalError> {
+        METER.with_borrow_mut(|meter| {
+            let Some(meter) = meter else {
+                return Ok(());
+            };
+
+            let (spent, limit) = match res {
+                Resource::Steps => (&mut meter.spent.steps, meter.budget.steps),
+                Resource::BuiltinCalls => {
+                    (&mut meter.spent.builtin_calls, meter.budget.builtin_calls)
+                }
+                Resource::Depth => (&mut meter.spent.depth, meter.budget.depth),
+            };
+
+            if *spent >= limit || meter.exhausted.is_some() {
+                let res = *meter.exhausted.get_or_insert(res);
+                return Err(EvalError::OutOfFuel(res));
+            }
+
+            *spent += 1;
+            Ok(())
+        })
+    }
+
+    pub fn enter() -> Result<DepthGuard, EvalError> {
+        Self::charge(Resource::Depth)?;
+        Ok(DepthGuard)
+    }
+
+    // If a nested evaluation ran out of fuel, builtins typically just fail,
+    // so we check this to report the true cause.
+    pub fn exhausted() -> Option<Resource> {
+        METER.with_borrow(|meter| meter.as_ref()?.exhausted)
+    }
+}
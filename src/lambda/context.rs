@@ -4,7 +4,7 @@
 
 use super::*;
 use rustc_hash::FxHashMap as HashMap;
-use std::{fmt::Debug, rc::Rc};
+use std::{fmt::Debug, sync::Arc};
 
 #[derive(Clone)]
 pub struct Context {
@@ -42,50 +42,80 @@ impl Context {
         vgen
     }
 
-    pub fn vars_producing<'a>(
-        &'a self,
-        ty: &'a Type,
-    ) -> impl Iterator<Item = (Identifier, Rc<Type>)> + 'a {
-        fn produces(ty: &Type, target: &Type) -> bool {
-            let ret_ty_produces = match ty {
-                Type::Fun(_, r) => produces(r, target),
-                _ => false,
-            };
-
-            ret_ty_produces || target == ty
-        }
+    // Polymorphic builtins are instantiated over the atomic types of the context.
+    pub fn vars_producing(&self, ty: &Type) -> VarsVec {
+        let ground = self.ground_types();
 
         self.defs
             .iter()
-            .filter_map(move |(v, BuiltIn { ty: t, .. })| {
-                if produces(t, ty) {
-                    Some((*v, t.clone()))
-                } else {
-                    None
-                }
+            .flat_map(|(v, builtin)| {
+                instances_producing(builtin, ty, &ground)
+                    .into_iter()
+                    .map(|inst| (*v, Arc::new(inst)))
             })
+            .collect()
+    }
+
+    pub fn ground_types(&self) -> Vec<Type> {
+        let mut ground = self
+            .defs
+            .values()
+            .flat_map(|builtin| ground_types(&builtin.ty, &builtin.forall))
+            .collect::<Vec<_>>();
+
+        ground.sort();
+        ground.dedup();
+        ground
     }
 
+    // Evaluates to weak head normal form, with no limit on resources.
+    // Panics if a builtin fails, or (when nested) the outer budget runs out, so builtins
+    // must use `try_evaluate` instead.
     pub fn evaluate(&self, term: &Term) -> Term {
-        let mut thunk: Thunk = term.clone().into();
-        self.evaluate_thunk(&mut thunk);
-        Rc::unwrap_or_clone(thunk).into_inner()
+        match self.try_evaluate(term) {
+            Ok(term) | Err(EvalError::Stuck(term)) => term,
+            Err(err) => panic!("Evaluation of `{}` failed: {}", term, err),
+        }
     }
 
-    pub fn evaluate_thunk(&self, thunk: &mut Thunk) {
-        use Term::*;
+    // Builtins should fail (with `None`) on an error, which is then reported as running
+    // out of fuel if that's what happened.
+    pub fn try_evaluate(&self, term: &Term) -> Result<Term, EvalError> {
+        self.evaluate_with(term, Budget::UNLIMITED)
+    }
+
+    // Nested evaluations (i.e., from within builtins) share the outermost budget.
+    pub fn evaluate_with(&self, term: &Term, budget: Budget) -> Result<Term, EvalError> {
+        let mut thunk: Thunk = Sharing::default().graph(term).into();
+
+        Meter::run(budget, || self.evaluate_thunk(&mut thunk))?;
+
+        let term = Arc::unwrap_or_clone(Sharing::default().read_back(&thunk));
+
+        if self.is_stuck(&term, 0) {
+            return Err(EvalError::Stuck(term));
+        }
+
+        Ok(term)
+    }
+
+    fn evaluate_thunk(&self, thunk: &mut Thunk) -> Result<(), EvalError> {
+        use Graph::*;
+        let _guard = Meter::enter()?;
         let mut borrow = (**thunk).borrow_mut();
         let term = &mut *borrow;
         match term {
-            Val(_) | Lam(_, _) => (),
+            Val(_) | Lam(_, _) => Ok(()),
             Var(v) => {
                 if let Some(BuiltIn {
                     func, n_args: 0, ..
                 }) = self.get(*v)
                 {
-                    *term = func(self, &mut []).unwrap();
+                    *term = self.call(*v, func, &[], &mut Sharing::default())?;
                     drop(borrow);
                     self.evaluate_thunk(thunk)
+                } else {
+                    Ok(())
                 }
             }
             Ref(next) => {
@@ -95,33 +125,39 @@ impl Context {
                 self.evaluate_thunk(thunk)
             }
             App(_, _) => {
-                self.collapse_spine(term, 0);
+                self.collapse_spine(term, 0)?;
+                Ok(())
             }
         }
     }
 
-    fn collapse_spine(&self, root: &mut Term, depth: usize) -> SpineCollapse {
+    fn collapse_spine(
+        &self,
+        root: &mut Graph,
+        depth: usize,
+    ) -> Result<SpineCollapse, EvalError> {
+        use Graph::*;
         use SpineCollapse::*;
-        use Term::*;
+        let _guard = Meter::enter()?;
         match root {
             Ref(thunk) => self.collapse_spine(&mut thunk.borrow_mut(), depth),
-            Val(_) | Lam(_, _) => Whnf,
+            Val(_) | Lam(_, _) => Ok(Whnf),
             Var(v) => match self.get(*v) {
                 Some(BuiltIn {
                     func, n_args: 0, ..
                 }) => {
-                    *root = func(self, &mut []).unwrap();
+                    *root = self.call(*v, func, &[], &mut Sharing::default())?;
                     self.collapse_spine(root, depth)
                 }
                 Some(blt) if blt.n_args <= depth => {
-                    Exec(blt.clone(), Vec::with_capacity(blt.n_args))
+                    Ok(Exec(*v, blt.clone(), Vec::with_capacity(blt.n_args)))
                 }
-                _ => Whnf,
+                _ => Ok(Whnf),
             },
             App(l, r) => {
                 let mut borr = l.borrow_mut();
-                match self.collapse_spine(&mut borr, depth + 1) {
-                    Exec(builtin, mut args) => {
+                match self.collapse_spine(&mut borr, depth + 1)? {
+                    Exec(ident, builtin, mut args) => {
                         drop(borr);
                         let argc = builtin.n_args;
 
@@ -131,22 +167,28 @@ impl Context {
                             args.reverse();
 
                             for arg in &mut args[..] {
-                                self.evaluate_thunk(arg);
+                                self.evaluate_thunk(arg)?;
                             }
 
-                            let func = &*builtin.func;
+                            // Shared subgraphs are read once, & linked back into the result
+                            let mut sharing = Sharing::default();
 
-                            if let Some(term) = func(self, &mut args[..]) {
-                                *root = term;
-                                return self.collapse_spine(root, depth);
-                            }
+                            let args = args
+                                .iter()
+                                .map(|arg| Arc::unwrap_or_clone(sharing.read_back(arg)))
+                                .collect::<Vec<_>>();
+
+                            *root = self.call(ident, &builtin.func, &args, &mut sharing)?;
+                            return self.collapse_spine(root, depth);
                         }
 
-                        Exec(builtin, args)
+                        Ok(Exec(ident, builtin, args))
                     }
                     Whnf => {
                         drop(borr);
-                        let borr = (**l).borrow();
+                        // The head may be an indirection to an abstraction (e.g. `(\x -> x) (\y -> y) z`)
+                        let l = follow_refs(l);
+                        let borr = (*l).borrow();
                         if let Lam(_, _) = *borr {
                             let Lam(v, b) = borr.clone() else {
                                 unreachable!()
@@ -154,22 +196,51 @@ impl Context {
 
                             drop(borr);
 
+                            Meter::charge(Resource::Steps)?;
                             *root = b.instantiate_var(v, r);
                             self.collapse_spine(root, depth)
                         } else {
                             drop(borr);
-                            Whnf
+                            Ok(Whnf)
                         }
                     }
                 }
             }
         }
     }
+
+    fn call(
+        &self,
+        ident: Identifier,
+        func: &BuiltInFunc,
+        args: &[Term],
+        sharing: &mut Sharing,
+    ) -> Result<Graph, EvalError> {
+        Meter::charge(Resource::BuiltinCalls)?;
+
+        match func(self, args) {
+            Some(term) => Ok(sharing.graph(&term)),
+            None => Err(match Meter::exhausted() {
+                Some(res) => EvalError::OutOfFuel(res),
+                None => EvalError::BuiltinFailed(ident),
+            }),
+        }
+    }
+
+    // A term in whnf is stuck unless it is a value, abstraction or partially applied builtin.
+    fn is_stuck(&self, term: &Term, argc: usize) -> bool {
+        use Term::*;
+        match term {
+            Val(_) | Lam(_, _) => argc > 0,
+            Var(v) => self.get(*v).is_none_or(|blt| blt.n_args <= argc),
+            App(l, _) => self.is_stuck(l, argc + 1),
+        }
+    }
 }
 
 enum SpineCollapse {
     Whnf,
-    Exec(BuiltIn, Vec<Thunk>),
+    Exec(Identifier, BuiltIn, Vec<Thunk>),
 }
 
 impl Debug for Context {
@@ -1,11 +1,27 @@
 use super::*;
-use std::rc::Rc;
+use std::sync::Arc;
 
-type BuiltInFunc = Rc<dyn Fn(&Context, &[Thunk]) -> Option<Term>>;
+pub type BuiltInFunc = Arc<dyn Fn(&Context, &[Term]) -> Option<Term> + Send + Sync>;
 
 #[derive(Clone)]
 pub struct BuiltIn {
     pub n_args: usize,
     pub func: BuiltInFunc,
-    pub ty: Rc<Type>,
+    pub ty: Arc<Type>,
+    // Type variables of `ty` which are universally quantified
+    pub forall: Vec<Identifier>,
+}
+
+impl BuiltIn {
+    // Replaces quantified variables with fresh ones
+    pub fn instantiate(&self) -> Type {
+        let mut ty = (*self.ty).clone();
+
+        if !self.forall.is_empty() {
+            let sub: TypeSub = self.forall.iter().map(|v| (*v, Type::Var(uuid()))).collect();
+            sub.apply(&mut ty);
+        }
+
+        ty
+    }
 }
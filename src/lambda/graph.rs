@@ -0,0 +1,136 @@
+// Mutable graph on which a single evaluation is performed.
+// Terms are copied in when evaluation starts & read back out when it ends (see `Sharing`),
+// so the sharing (& interior mutability) here never escapes to other threads.
+
+use super::*;
+use rustc_hash::FxHashMap as HashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub(super) type Thunk = Rc<RefCell<Graph>>;
+
+#[derive(Clone, Debug)]
+pub(super) enum Graph {
+    Val(Value),
+    Var(Identifier),
+    Lam(Identifier, Rc<Graph>),
+    App(Thunk, Thunk),
+
+    // Ref:
+    // Transparent indirection to another node (May be deleted in the process of other operations)
+    // Needed for a faithful implementation of graph reduction.
+    Ref(Thunk),
+}
+
+impl Graph {
+    pub fn instantiate_var(&self, var: Identifier, thunk: &Thunk) -> Graph {
+        use Graph::*;
+        match self {
+            Val(n) => Val(n.clone()),
+            Lam(v, b) => {
+                if *v == var {
+                    Lam(*v, b.clone())
+                } else {
+                    Lam(*v, b.instantiate_var(var, thunk).into())
+                }
+            }
+            Var(v) => {
+                if *v == var {
+                    Ref(thunk.clone())
+                } else {
+                    Var(*v)
+                }
+            }
+            Ref(next) => {
+                let next = &*(**next).borrow();
+                next.instantiate_var(var, thunk)
+            }
+            App(l, r) => App(
+                (**l).borrow().instantiate_var(var, thunk).into(),
+                (**r).borrow().instantiate_var(var, thunk).into(),
+            ),
+        }
+    }
+}
+
+// Thunks read back into terms, & the terms they became. Reading back through this keeps
+// the sharing of the graph (each thunk is read once), & graphs made from the terms (e.g.
+// the results of builtins) link back to the thunks instead of copying them.
+#[derive(Default)]
+pub(super) struct Sharing {
+    terms: HashMap<*const RefCell<Graph>, Arc<Term>>,
+    thunks: HashMap<*const Term, Thunk>,
+}
+
+impl Sharing {
+    pub fn read_back(&mut self, thunk: &Thunk) -> Arc<Term> {
+        let thunk = follow_refs(thunk);
+        let ptr = Rc::as_ptr(&thunk);
+
+        if let Some(term) = self.terms.get(&ptr) {
+            return term.clone();
+        }
+
+        let term = Arc::new(self.read_node(&thunk.borrow()));
+
+        self.thunks.insert(Arc::as_ptr(&term), thunk.clone());
+        self.terms.insert(ptr, term.clone());
+        term
+    }
+
+    fn read_node(&mut self, graph: &Graph) -> Term {
+        use Graph::*;
+        match graph {
+            Ref(r) => Arc::unwrap_or_clone(self.read_back(r)),
+            Val(n) => Term::Val(n.clone()),
+            Var(v) => Term::Var(*v),
+            Lam(v, b) => Term::Lam(*v, self.read_node(b).into()),
+            App(l, r) => Term::App(self.read_back(l), self.read_back(r)),
+        }
+    }
+
+    pub fn graph(&mut self, term: &Term) -> Graph {
+        match term {
+            Term::Val(n) => Graph::Val(n.clone()),
+            Term::Var(v) => Graph::Var(*v),
+            Term::Lam(v, b) => Graph::Lam(*v, Rc::new(self.graph(b))),
+            Term::App(l, r) => Graph::App(self.thunk(l), self.thunk(r)),
+        }
+    }
+
+    fn thunk(&mut self, term: &Arc<Term>) -> Thunk {
+        if let Some(thunk) = self.thunks.get(&Arc::as_ptr(term)) {
+            return thunk.clone();
+        }
+
+        let thunk: Thunk = self.graph(term).into();
+
+        // Kept alive, so the pointer isn't reused
+        self.terms.insert(Rc::as_ptr(&thunk), term.clone());
+        self.thunks.insert(Arc::as_ptr(term), thunk.clone());
+        thunk
+    }
+}
+
+// Skips over any indirections
+pub(super) fn follow_refs(thunk: &Thunk) -> Thunk {
+    let mut thunk = thunk.clone();
+
+    loop {
+        let next = match &*thunk.borrow() {
+            Graph::Ref(next) => next.clone(),
+            _ => break,
+        };
+
+        thunk = next;
+    }
+
+    thunk
+}
+
+impl From<Graph> for Thunk {
+    fn from(value: Graph) -> Self {
+        Rc::new(value.into())
+    }
+}
@@ -1,12 +1,17 @@
+pub mod budget;
 pub mod context;
 pub mod env;
+mod graph;
 pub mod parser;
 pub mod vars;
 pub mod term;
 
 pub use super::*;
+pub use budget::*;
 pub use context::*;
 pub use env::*;
+use graph::*;
+pub use parser::*;
 pub use vars::*;
 pub use term::*;
 
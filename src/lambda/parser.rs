@@ -13,7 +13,7 @@ macro_rules! term {
 		$x.clone()
 	};
 	($x: literal) => {
-		$crate::Term::Val(std::rc::Rc::new($x))
+		$crate::Term::Val(std::sync::Arc::new($x))
 	};
 	($x:ident -> $($r:tt)+) => {
 		$crate::Term::Lam($crate::Identifier::Name(stringify!($x)), $crate::term!($($r)+).into())
@@ -38,15 +38,22 @@ macro_rules! term {
 	}};
 }
 
+// Each argument type must be a single token tree, so constructor types need parentheses:
+// `builtin!(forall a. a => (List a) => (List a) |x, xs| => ...)`
 #[macro_export]
 macro_rules! builtin {
+	(forall $($var:ident)+ . $($rest:tt)+) => {{
+		let mut builtin = $crate::builtin!($($rest)+);
+		builtin.forall = vec![$($crate::Identifier::Name(stringify!($var))),+];
+		builtin
+	}};
 	(
 		$($ty:tt)=>+
 		$(with [$($captured:ident),+] in)?
     $($ctxt:ident)? |$($arg:ident),*| => $body:expr
 	) => {{
 		use $crate::*;
-		use std::rc::Rc;
+		use std::sync::Arc;
 
 		let ty = ty!($($ty)=>+);
 
@@ -56,7 +63,7 @@ macro_rules! builtin {
 			let $captured = $captured.clone();
 		)+)?
 
-		let func = Rc::new(move |_ctxt: &Context, _args: &[Thunk]| {
+		let func = Arc::new(move |_ctxt: &Context, _args: &[Term]| {
 			let rev_list!([$($arg),*]) = &_args[..] else {
 				unreachable!()
 			};
@@ -65,17 +72,14 @@ macro_rules! builtin {
         let $ctxt = _ctxt;
       )?
 
-			$(
-				let $arg = (**$arg).borrow();
-			)*
-
 			Some($body)
 		});
 
 		BuiltIn {
 			n_args,
 			func,
-			ty: std::rc::Rc::new(ty)
+			ty: Arc::new(ty),
+			forall: vec![],
 		}
 	}}
 }
@@ -93,6 +97,16 @@ macro_rules! context {
 
 #[macro_export]
 macro_rules! ty {
+	// Constructor arguments are collected up to the next `=>`
+	(@con $c:ident [$($args:tt)+] => $($rest:tt)+) => {
+		$crate::Type::Fun($crate::ty!(@con $c [$($args)+]).into(), $crate::ty!($($rest)+).into())
+	};
+	(@con $c:ident [$($args:tt)+] $next:tt $($rest:tt)*) => {
+		$crate::ty!(@con $c [$($args)+ $next] $($rest)*)
+	};
+	(@con $c:ident [$($args:tt)+]) => {
+		$crate::Type::Con(Identifier::from(stringify!($c)), vec![$($crate::ty!($args).into()),+])
+	};
 	([$e:expr]) => {
 		$e
 	};
@@ -105,6 +119,9 @@ macro_rules! ty {
 	(($($r:tt)+)) => {
 		ty!($($r)+)
 	};
+	($c:ident $arg:tt $($rest:tt)*) => {
+		$crate::ty!(@con $c [$arg] $($rest)*)
+	};
 }
 
 #[macro_export]
@@ -122,3 +139,314 @@ macro_rules! count {
     () => { 0 };
     ($x:ident $($xs:ident)*) => { 1 + $crate::count!($($xs)*)};
 }
+
+// Runtime parser for the syntax printed by `Term`'s & `Type`'s `Display` impls.
+// Application is juxtaposition, so both `mult(p)(plus(one)(one))` and
+// `mult p (plus one one)` are accepted.
+
+use super::*;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+	pub pos: usize, // Byte offset into the source
+	pub msg: String,
+}
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "parse error at {}: {}", self.pos, self.msg)
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+// Interprets literal tokens (numbers, `true`, ...) as values.
+pub type LiteralParser<'a> = &'a dyn Fn(&str) -> Option<Value>;
+
+// Numbers become `i32`s, `true` & `false` become `bool`s.
+pub fn default_literals(tok: &str) -> Option<Value> {
+	match tok {
+		"true" => Some(std::sync::Arc::new(true)),
+		"false" => Some(std::sync::Arc::new(false)),
+		_ => tok.parse::<i32>().ok().map(|n| std::sync::Arc::new(n) as Value),
+	}
+}
+
+pub fn parse_term(src: &str) -> Result<Term, ParseError> {
+	parse_term_with(src, &default_literals)
+}
+
+pub fn parse_term_with(src: &str, literals: LiteralParser) -> Result<Term, ParseError> {
+	let mut parser = Parser::new(src)?;
+	let term = parser.term(literals)?;
+	parser.finish()?;
+	Ok(term)
+}
+
+pub fn parse_type(src: &str) -> Result<Type, ParseError> {
+	let mut parser = Parser::new(src)?;
+	let ty = parser.ty()?;
+	parser.finish()?;
+	Ok(ty)
+}
+
+impl FromStr for Term {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_term(s)
+	}
+}
+
+impl FromStr for Type {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_type(s)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token<'s> {
+	LParen,
+	RParen,
+	Lambda,
+	Arrow,    // ->
+	FatArrow, // =>
+	Word(&'s str),
+	End,
+}
+
+impl Display for Token<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use Token::*;
+		match self {
+			LParen => write!(f, "`(`"),
+			RParen => write!(f, "`)`"),
+			Lambda => write!(f, "`\\`"),
+			Arrow => write!(f, "`->`"),
+			FatArrow => write!(f, "`=>`"),
+			Word(w) => write!(f, "`{}`", w),
+			End => write!(f, "end of input"),
+		}
+	}
+}
+
+struct Parser<'s> {
+	tokens: Vec<(usize, Token<'s>)>,
+	next: usize,
+}
+
+impl<'s> Parser<'s> {
+	fn new(src: &'s str) -> Result<Self, ParseError> {
+		use Token::*;
+
+		let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+		let mut tokens = vec![];
+		let mut chars = src.char_indices().peekable();
+
+		while let Some((pos, c)) = chars.next() {
+			let token = match c {
+				c if c.is_whitespace() => continue,
+				'(' => LParen,
+				')' => RParen,
+				// Not `λ`, which is a variable name (see `IDENTS`)
+				'\\' => Lambda,
+				'=' if chars.next_if(|&(_, c)| c == '>').is_some() => FatArrow,
+				'-' if chars.next_if(|&(_, c)| c == '>').is_some() => Arrow,
+				c if is_word(c) || c == '-' => {
+					let mut end = pos + c.len_utf8();
+					while let Some((i, c)) = chars.next_if(|&(_, c)| is_word(c)) {
+						end = i + c.len_utf8();
+					}
+					Word(&src[pos..end])
+				}
+				c => {
+					return Err(ParseError {
+						pos,
+						msg: format!("unexpected character `{}`", c),
+					})
+				}
+			};
+
+			tokens.push((pos, token));
+		}
+
+		tokens.push((src.len(), End));
+
+		Ok(Self { tokens, next: 0 })
+	}
+
+	fn peek(&self) -> Token<'s> {
+		self.tokens[self.next].1
+	}
+
+	fn pos(&self) -> usize {
+		self.tokens[self.next].0
+	}
+
+	fn bump(&mut self) -> Token<'s> {
+		let token = self.peek();
+		if token != Token::End {
+			self.next += 1;
+		}
+		token
+	}
+
+	fn error<T>(&self, expected: &str) -> Result<T, ParseError> {
+		Err(ParseError {
+			pos: self.pos(),
+			msg: format!("expected {}, found {}", expected, self.peek()),
+		})
+	}
+
+	fn expect(&mut self, token: Token<'s>) -> Result<(), ParseError> {
+		if self.peek() == token {
+			self.bump();
+			Ok(())
+		} else {
+			self.error(&token.to_string())
+		}
+	}
+
+	fn finish(&mut self) -> Result<(), ParseError> {
+		self.expect(Token::End)
+	}
+
+	fn ident(&mut self) -> Result<Identifier, ParseError> {
+		let pos = self.pos();
+		let Token::Word(word) = self.peek() else {
+			return self.error("identifier");
+		};
+
+		let ident = ident_from_str(word).ok_or_else(|| ParseError {
+			pos,
+			msg: format!("invalid identifier `{}`", word),
+		})?;
+
+		self.bump();
+		Ok(ident)
+	}
+
+	fn term(&mut self, literals: LiteralParser) -> Result<Term, ParseError> {
+		use Token::*;
+
+		if self.peek() == Lambda {
+			self.bump();
+
+			let mut params = vec![self.ident()?];
+			while let Word(_) = self.peek() {
+				params.push(self.ident()?);
+			}
+
+			self.expect(Arrow)?;
+
+			let body = self.term(literals)?;
+
+			let lam = params
+				.into_iter()
+				.rev()
+				.fold(body, |body, v| Term::Lam(v, body.into()));
+
+			return Ok(lam);
+		}
+
+		let mut head = self.atom(literals)?;
+
+		loop {
+			let arg = match self.peek() {
+				Lambda => self.term(literals)?,
+				LParen | Word(_) => self.atom(literals)?,
+				_ => return Ok(head),
+			};
+
+			head = Term::App(head.into(), arg.into());
+		}
+	}
+
+	fn atom(&mut self, literals: LiteralParser) -> Result<Term, ParseError> {
+		use Token::*;
+		match self.peek() {
+			LParen => {
+				self.bump();
+				let term = self.term(literals)?;
+				self.expect(RParen)?;
+				Ok(term)
+			}
+			Word(word) => {
+				if let Some(val) = literals(word) {
+					self.bump();
+					Ok(Term::Val(val))
+				} else {
+					Ok(Term::Var(self.ident()?))
+				}
+			}
+			_ => self.error("term"),
+		}
+	}
+
+	fn ty(&mut self) -> Result<Type, ParseError> {
+		let lhs = self.ty_app()?;
+
+		if self.peek() == Token::FatArrow {
+			self.bump();
+			let rhs = self.ty()?;
+			Ok(Type::Fun(lhs.into(), rhs.into()))
+		} else {
+			Ok(lhs)
+		}
+	}
+
+	// Constructor application binds tighter than `=>`, so `List N => N` is a function.
+	fn ty_app(&mut self) -> Result<Type, ParseError> {
+		let start = self.pos();
+		let head = self.ty_atom()?;
+
+		let mut args = vec![];
+		while matches!(self.peek(), Token::Word(_) | Token::LParen) {
+			args.push(self.ty_atom()?.into());
+		}
+
+		match head {
+			_ if args.is_empty() => Ok(head),
+			Type::Var(c @ Identifier::Name(_)) => Ok(Type::Con(c, args)),
+			_ => Err(ParseError {
+				pos: start,
+				msg: format!("`{}` is not a type constructor", head),
+			}),
+		}
+	}
+
+	fn ty_atom(&mut self) -> Result<Type, ParseError> {
+		use Token::*;
+		match self.peek() {
+			LParen => {
+				self.bump();
+				let ty = self.ty()?;
+				self.expect(RParen)?;
+				Ok(ty)
+			}
+			Word(_) => Ok(Type::Var(self.ident()?)),
+			_ => self.error("type"),
+		}
+	}
+}
+
+// Inverse of `Identifier`'s `Display` impl.
+fn ident_from_str(word: &str) -> Option<Identifier> {
+	let mut chars = word.chars();
+	let first = chars.next()?;
+
+	if first == '_' && word.len() > 1 {
+		return word[1..].parse::<u128>().ok().map(Identifier::Uuid);
+	}
+
+	if !(first.is_alphabetic() || first == '_') {
+		return None;
+	}
+
+	Some(Identifier::intern(word))
+}
@@ -15,6 +15,28 @@ impl Identifier {
             _ => unreachable!(),
         }
     }
+
+    // Names built at runtime (e.g. by the parser) are leaked once & reused.
+    pub fn intern(name: &str) -> Self {
+        use std::sync::{Mutex, OnceLock};
+
+        if let Some(&ident) = IDENTS.iter().find(|&&s| s == name) {
+            return Self::Name(ident);
+        }
+
+        static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+        let mut names = NAMES.get_or_init(Default::default).lock().unwrap();
+
+        if let Some(&name) = names.get(name) {
+            return Self::Name(name);
+        }
+
+        let name: &'static str = name.to_string().leak();
+        names.insert(name);
+
+        Self::Name(name)
+    }
 }
 
 //A few letters removed for legibility (N, omicron, nu, upsilon, )
@@ -81,17 +103,11 @@ pub fn new_var_where(
 }
 
 pub fn uuid() -> Identifier {
-    // Technically unsafe in a multithreaded setting, but I'll be shocked if this isn't fine forever.
-
-    static mut COUNTER: u128 = 0;
+    use std::sync::atomic::{AtomicU64, Ordering};
 
-    let x;
-    unsafe {
-        x = COUNTER;
-        COUNTER += 1;
-    }
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
 
-    Identifier::Uuid(x)
+    Identifier::Uuid(COUNTER.fetch_add(1, Ordering::Relaxed) as u128)
 }
 
 impl Display for Identifier {
@@ -0,0 +1,556 @@
+// Library learning: compressing a corpus by abstracting out shared fragments.
+// Candidate fragments are anti-unifications of pairs of subterms (of the same shape), in
+// which the parts that differ become holes. The fragment saving the most size across the corpus becomes a new
+// builtin, the corpus is rewritten to use it, & the process repeats (so that inventions
+// may build on each other).
+
+use super::*;
+
+use rustc_hash::FxHashMap as HashMap;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fragment {
+    Hole(usize),
+    // Values & variables (either builtins or bound within the fragment)
+    Leaf(Term),
+    Lam(Identifier, Arc<Fragment>),
+    App(Arc<Fragment>, Arc<Fragment>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Invention {
+    pub name: Identifier,
+    pub fragment: Fragment,
+    // Abstracts over the holes of `fragment`, in order
+    pub definition: Term,
+    pub ty: Type,
+    // Reduction in the size of the corpus, net of the size of `definition`
+    pub savings: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Library {
+    pub inventions: Vec<Invention>,
+}
+
+// Returns the library & the corpus rewritten to use it.
+pub fn compress(ctxt: &Context, corpus: &[Term], max_inventions: usize) -> (Library, Vec<Term>) {
+    let mut ctxt = ctxt.clone();
+    let mut library = Library::default();
+    let mut corpus = corpus.to_vec();
+
+    while library.inventions.len() < max_inventions {
+        let name = (library.inventions.len()..)
+            .map(|i| Identifier::intern(&format!("lib{}", i)))
+            .find(|&name| ctxt.get(name).is_none())
+            .unwrap();
+
+        let best = candidates(&ctxt, &corpus)
+            .into_iter()
+            .find_map(|(fragment, savings)| {
+                let definition = fragment.definition(&ctxt);
+                let ty = infer(&ctxt, &definition).ok()?;
+
+                Some(Invention {
+                    name,
+                    fragment,
+                    definition,
+                    ty,
+                    savings,
+                })
+            });
+
+        let Some(invention) = best else {
+            break;
+        };
+
+        corpus = corpus
+            .iter()
+            .map(|term| invention.fragment.rewrite(term, name))
+            .collect();
+
+        ctxt.insert(&[(name, invention.builtin())]);
+        library.inventions.push(invention);
+    }
+
+    (library, corpus)
+}
+
+// Fragments which compress the corpus, from most to least savings. Only subterms of the
+// same shape are anti-unified, & each fragment is scored on the terms containing a match.
+fn candidates(ctxt: &Context, corpus: &[Term]) -> Vec<(Fragment, usize)> {
+    let mut subterms = vec![];
+    // The corpus terms each subterm occurs in
+    let mut containing: Vec<Vec<usize>> = vec![];
+    let mut ids = HashMap::default();
+
+    for (i, term) in corpus.iter().enumerate() {
+        collect_subterms(term, &mut |sub| {
+            if matches!(sub, Term::Val(_) | Term::Var(_)) {
+                return;
+            }
+
+            let id = *ids.entry(sub.to_string()).or_insert_with(|| {
+                subterms.push(sub.clone());
+                containing.push(vec![]);
+                subterms.len() - 1
+            });
+
+            if containing[id].last() != Some(&i) {
+                containing[id].push(i);
+            }
+        });
+    }
+
+    let mut buckets: HashMap<Shape, Vec<usize>> = HashMap::default();
+
+    for (id, sub) in subterms.iter().enumerate() {
+        buckets.entry(Shape::of(ctxt, sub)).or_default().push(id);
+    }
+
+    // With the shape they were found at
+    let mut fragments = HashMap::default();
+
+    for (shape, bucket) in &buckets {
+        for (i, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[i..] {
+                let mut generalization = Generalization {
+                    ctxt,
+                    holes: vec![],
+                    bound: vec![],
+                };
+
+                if let Some(fragment) = generalization.lgg(&subterms[a], &subterms[b]) {
+                    fragments
+                        .entry(fragment.to_string())
+                        .or_insert((fragment, shape));
+                }
+            }
+        }
+    }
+
+    let mut candidates = fragments
+        .into_values()
+        .filter_map(|(fragment, shape)| {
+            // Applications of holes to holes can never save anything
+            if fragment.size() <= fragment.holes() + 1 {
+                return None;
+            }
+
+            // A hole at the head may stand for part of a longer spine (or a whole body)
+            let ids = match fragment.head() {
+                Fragment::Hole(_) => (0..subterms.len()).collect(),
+                _ => buckets[shape].clone(),
+            };
+
+            let mut affected = ids
+                .into_iter()
+                .filter(|&id| fragment.match_term(&subterms[id]).is_some())
+                .flat_map(|id| containing[id].iter().copied())
+                .collect::<Vec<_>>();
+
+            affected.sort();
+            affected.dedup();
+
+            let saved = affected
+                .iter()
+                .map(|&i| corpus[i].size() - fragment.rewrite(&corpus[i], "lib".into()).size())
+                .sum::<usize>();
+
+            let savings = saved
+                .checked_sub(fragment.definition(ctxt).size())
+                .filter(|&s| s > 0)?;
+
+            Some((fragment, savings))
+        })
+        .collect::<Vec<_>>();
+
+    // Ties are broken by the printed fragment, so that the order is deterministic
+    candidates.sort_by_cached_key(|(fragment, savings)| {
+        (std::cmp::Reverse(*savings), fragment.to_string())
+    });
+
+    candidates
+}
+
+// Anti-unifying terms of different shapes leaves a hole at the root (or head), so only
+// subterms of the same shape are paired up.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Shape {
+    lambdas: usize,
+    // The builtin or value at the head of the body, unless it's bound
+    head: Option<String>,
+    args: usize,
+}
+
+impl Shape {
+    fn of(ctxt: &Context, term: &Term) -> Self {
+        let mut shape = Self {
+            lambdas: 0,
+            head: None,
+            args: 0,
+        };
+
+        let mut body = term;
+
+        while let Term::Lam(_, b) = body {
+            shape.lambdas += 1;
+            body = b;
+        }
+
+        while let Term::App(l, _) = body {
+            shape.args += 1;
+            body = l;
+        }
+
+        shape.head = match body {
+            Term::Var(v) if ctxt.get(*v).is_some() => Some(body.to_string()),
+            Term::Val(_) => Some(body.to_string()),
+            _ => None,
+        };
+
+        shape
+    }
+}
+
+fn collect_subterms(term: &Term, f: &mut impl FnMut(&Term)) {
+    f(term);
+
+    match term {
+        Term::Lam(_, b) => collect_subterms(b, f),
+        Term::App(l, r) => {
+            collect_subterms(l, f);
+            collect_subterms(r, f);
+        }
+        _ => (),
+    }
+}
+
+fn occurs_free(var: Identifier, term: &Term) -> bool {
+    use Term::*;
+    match term {
+        Val(_) => false,
+        Var(v) => *v == var,
+        Lam(v, b) => *v != var && occurs_free(var, b),
+        App(l, r) => occurs_free(var, l) || occurs_free(var, r),
+    }
+}
+
+// Least general generalization of two terms, up to renaming of bound variables.
+// Fails if the terms differ inside an abstraction, at a subterm mentioning its variable.
+struct Generalization<'a> {
+    ctxt: &'a Context,
+    // Pairs of subterms abstracted by each hole
+    holes: Vec<(&'a Term, &'a Term)>,
+    // Pairs of corresponding variables bound within the fragment
+    bound: Vec<(Identifier, Identifier)>,
+}
+
+impl<'a> Generalization<'a> {
+    fn lgg(&mut self, a: &'a Term, b: &'a Term) -> Option<Fragment> {
+        use Term::*;
+        match (a, b) {
+            (App(la, ra), App(lb, rb)) => Some(Fragment::App(
+                self.lgg(la, lb)?.into(),
+                self.lgg(ra, rb)?.into(),
+            )),
+            (Lam(v, ba), Lam(w, bb)) => {
+                self.bound.push((*v, *w));
+                let body = self.lgg(ba, bb);
+                self.bound.pop();
+
+                Some(Fragment::Lam(*v, body?.into()))
+            }
+            (Var(v), Var(w)) => match self.bound.iter().rev().find(|(x, y)| x == v || y == w) {
+                Some((x, y)) if x == v && y == w => Some(Fragment::Leaf(a.clone())),
+                Some(_) => None,
+                None if v == w && self.ctxt.get(*v).is_some() => Some(Fragment::Leaf(a.clone())),
+                None => self.hole(a, b),
+            },
+            (Val(_), Val(_)) if a == b => Some(Fragment::Leaf(a.clone())),
+            _ => self.hole(a, b),
+        }
+    }
+
+    fn hole(&mut self, a: &'a Term, b: &'a Term) -> Option<Fragment> {
+        let captured = self
+            .bound
+            .iter()
+            .any(|(x, y)| occurs_free(*x, a) || occurs_free(*y, b));
+
+        if captured {
+            return None;
+        }
+
+        let i = match self.holes.iter().position(|&(c, d)| c == a && d == b) {
+            Some(i) => i,
+            None => {
+                self.holes.push((a, b));
+                self.holes.len() - 1
+            }
+        };
+
+        Some(Fragment::Hole(i))
+    }
+}
+
+impl Fragment {
+    pub fn holes(&self) -> usize {
+        use Fragment::*;
+        match self {
+            Hole(i) => i + 1,
+            Leaf(_) => 0,
+            Lam(_, b) => b.holes(),
+            App(l, r) => l.holes().max(r.holes()),
+        }
+    }
+
+    // Of the body, under any abstractions (as in `Shape`)
+    fn head(&self) -> &Fragment {
+        let mut body = self;
+
+        while let Fragment::Lam(_, b) = body {
+            body = b;
+        }
+
+        while let Fragment::App(l, _) = body {
+            body = l;
+        }
+
+        body
+    }
+
+    // Size, not counting holes
+    pub fn size(&self) -> usize {
+        use Fragment::*;
+        match self {
+            Hole(_) => 0,
+            Leaf(_) => 1,
+            Lam(_, b) => 1 + b.size(),
+            App(l, r) => 1 + l.size() + r.size(),
+        }
+    }
+
+    // Subterms of `term` filling each hole, if it is an instance of the fragment
+    pub fn match_term(&self, term: &Term) -> Option<Vec<Term>> {
+        let mut bindings = vec![None; self.holes()];
+
+        if self.bind(term, &mut bindings, &mut vec![]) {
+            bindings.into_iter().collect()
+        } else {
+            None
+        }
+    }
+
+    fn bind(
+        &self,
+        term: &Term,
+        bindings: &mut [Option<Term>],
+        bound: &mut Vec<(Identifier, Identifier)>,
+    ) -> bool {
+        use Fragment::*;
+        match (self, term) {
+            (Hole(i), _) => {
+                if bound.iter().any(|(_, y)| occurs_free(*y, term)) {
+                    return false;
+                }
+
+                match &bindings[*i] {
+                    Some(prev) => prev == term,
+                    None => {
+                        bindings[*i] = Some(term.clone());
+                        true
+                    }
+                }
+            }
+            (Leaf(Term::Var(v)), Term::Var(w)) => {
+                match bound.iter().rev().find(|(x, y)| x == v || y == w) {
+                    Some((x, y)) => x == v && y == w,
+                    None => v == w,
+                }
+            }
+            (Leaf(leaf), _) => leaf == term,
+            (Lam(v, fb), Term::Lam(w, tb)) => {
+                bound.push((*v, *w));
+                let bound_body = fb.bind(tb, bindings, bound);
+                bound.pop();
+                bound_body
+            }
+            (App(fl, fr), Term::App(tl, tr)) => {
+                fl.bind(tl, bindings, bound) && fr.bind(tr, bindings, bound)
+            }
+            _ => false,
+        }
+    }
+
+    // Replaces instances of the fragment (outermost first) with applications of `name`
+    pub fn rewrite(&self, term: &Term, name: Identifier) -> Term {
+        if let Some(args) = self.match_term(term) {
+            return args.iter().fold(Term::Var(name), |app, arg| {
+                Term::App(app.into(), self.rewrite(arg, name).into())
+            });
+        }
+
+        match term {
+            Term::Lam(v, b) => Term::Lam(*v, self.rewrite(b, name).into()),
+            Term::App(l, r) => {
+                Term::App(self.rewrite(l, name).into(), self.rewrite(r, name).into())
+            }
+            _ => term.clone(),
+        }
+    }
+
+    // Closed term taking the holes as arguments. Bound variables are renamed apart from
+    // the builtins of `ctxt`.
+    pub fn definition(&self, ctxt: &Context) -> Term {
+        fn core(
+            fragment: &Fragment,
+            params: &[Identifier],
+            renaming: &mut Vec<(Identifier, Identifier)>,
+            vgen: &mut VarGen,
+        ) -> Term {
+            use Fragment::*;
+            match fragment {
+                Hole(i) => Term::Var(params[*i]),
+                Leaf(Term::Var(v)) => match renaming.iter().rev().find(|(x, _)| x == v) {
+                    Some((_, y)) => Term::Var(*y),
+                    None => Term::Var(*v),
+                },
+                Leaf(leaf) => leaf.clone(),
+                Lam(v, b) => {
+                    let w = vgen.small_var();
+
+                    renaming.push((*v, w));
+                    let body = core(b, params, renaming, vgen);
+                    renaming.pop();
+
+                    Term::Lam(w, body.into())
+                }
+                App(l, r) => Term::App(
+                    core(l, params, renaming, vgen).into(),
+                    core(r, params, renaming, vgen).into(),
+                ),
+            }
+        }
+
+        let mut vgen = ctxt.vgen();
+        let params = (0..self.holes())
+            .map(|_| vgen.small_var())
+            .collect::<Vec<_>>();
+
+        let body = core(self, &params, &mut vec![], &mut vgen);
+
+        params
+            .iter()
+            .rev()
+            .fold(body, |body, v| Term::Lam(*v, body.into()))
+    }
+}
+
+impl Invention {
+    // Calls reduce to the definition itself, so that arguments are only evaluated on demand.
+    pub fn builtin(&self) -> BuiltIn {
+        let definition = self.definition.clone();
+
+        let forall = self
+            .ty
+            .vars()
+            .into_iter()
+            .filter(|v| matches!(v, Identifier::Uuid(_)))
+            .collect();
+
+        BuiltIn {
+            n_args: 0,
+            func: Arc::new(move |_, _| Some(definition.clone())),
+            ty: Arc::new(self.ty.clone()),
+            forall,
+        }
+    }
+}
+
+impl Library {
+    pub fn get(&self, name: Identifier) -> Option<&Invention> {
+        self.inventions.iter().find(|inv| inv.name == name)
+    }
+
+    pub fn extend(&self, ctxt: &mut Context) {
+        let defs = self
+            .inventions
+            .iter()
+            .map(|inv| (inv.name, inv.builtin()))
+            .collect::<Vec<_>>();
+
+        ctxt.insert(&defs);
+    }
+}
+
+impl Display for Fragment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Fragment::*;
+        match self {
+            Hole(i) => write!(f, "?{}", i),
+            Leaf(leaf) => write!(f, "{}", leaf),
+            Lam(v, b) => write!(f, "(\\{} -> {})", v, b),
+            App(l, r) => write!(f, "{}({})", l, r),
+        }
+    }
+}
+
+// Extends the context of a language with a library.
+#[derive(Clone, Debug)]
+pub struct WithLibrary<L: Language> {
+    pub lang: L,
+    pub library: Library,
+    ctxt: Context,
+}
+
+impl<L: Language> WithLibrary<L> {
+    pub fn new(lang: L, library: Library) -> Self {
+        let mut ctxt = lang.context();
+        library.extend(&mut ctxt);
+
+        Self {
+            lang,
+            library,
+            ctxt,
+        }
+    }
+}
+
+impl<L: Language> Language for WithLibrary<L> {
+    type Semantics = L::Semantics;
+
+    const SMALL_SIZE: usize = L::SMALL_SIZE;
+    const LARGE_SIZE: usize = L::LARGE_SIZE;
+
+    fn context(&self) -> Context {
+        self.ctxt.clone()
+    }
+
+    fn prior(&self) -> Prior {
+        self.lang.prior()
+    }
+
+    fn sval(&self, val: &Value, ty: &Type) -> Analysis<Self> {
+        self.lang.sval(val, ty).cast()
+    }
+
+    // Definitions may lie outside the terms the semantics were written for (e.g. they may
+    // be higher-order), so uses of inventions are never grouped.
+    fn svar(&self, ident: Identifier, ty: &Type) -> Analysis<Self> {
+        match self.library.get(ident) {
+            Some(_) => Analysis::Unique,
+            None => self.lang.svar(ident, ty).cast(),
+        }
+    }
+
+    fn slam(&self, ident: Identifier, body: Analysis<Self>, ty: &Type) -> Analysis<Self> {
+        self.lang.slam(ident, body.cast(), ty).cast()
+    }
+
+    fn sapp(&self, fun: Analysis<Self>, arg: Analysis<Self>, ty: &Type) -> Analysis<Self> {
+        self.lang.sapp(fun.cast(), arg.cast(), ty).cast()
+    }
+}
@@ -0,0 +1,239 @@
+// Fitting priors to a corpus of solved programs.
+// Terms are read as trees of heads: the spine `h a1 .. an` is a node labelled `h`, whose
+// children are the arguments (under any abstractions). Head probabilities are fit by
+// maximum likelihood with additive smoothing, either outright (`Unigram`) or given the
+// parent head & argument position (`Bigram`).
+
+mod library;
+
+pub use library::*;
+
+use super::*;
+
+use rustc_hash::FxHashMap as HashMap;
+use std::fmt::{self, Display};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Head {
+    Builtin(Identifier),
+    // Variables bound by abstractions (or by the search), which are interchangeable
+    Bound,
+    Val,
+}
+
+// Head of the parent spine & the position of the argument within it
+pub type Parent = Option<(Head, usize)>;
+
+#[derive(Clone, Debug)]
+pub struct Unigram {
+    log_probs: HashMap<Head, f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Bigram {
+    unigram: Unigram,
+    counts: HashMap<(Parent, Head), f64>,
+    totals: HashMap<Parent, f64>,
+    smoothing: f64,
+}
+
+impl Head {
+    fn of(ctxt: &Context, term: &Term) -> Self {
+        match term {
+            Term::Var(v) if ctxt.get(*v).is_some() => Head::Builtin(*v),
+            Term::Val(_) => Head::Val,
+            _ => Head::Bound,
+        }
+    }
+}
+
+impl Display for Head {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Head::Builtin(v) => write!(f, "{}", v),
+            Head::Bound => write!(f, "<var>"),
+            Head::Val => write!(f, "<val>"),
+        }
+    }
+}
+
+// Every head that can be chosen in `ctxt`
+fn heads(ctxt: &Context) -> Vec<Head> {
+    let mut heads = ctxt
+        .iter()
+        .map(|(&v, _)| Head::Builtin(v))
+        .chain([Head::Bound, Head::Val])
+        .collect::<Vec<_>>();
+
+    heads.sort();
+    heads
+}
+
+// Calls `f` on each spine of `term`, with the head of its parent spine.
+pub fn visit_heads(ctxt: &Context, term: &Term, f: &mut impl FnMut(Parent, Head)) {
+    fn core(ctxt: &Context, term: &Term, parent: Parent, f: &mut impl FnMut(Parent, Head)) {
+        let mut term = term;
+
+        while let Term::Lam(_, b) = term {
+            term = b;
+        }
+
+        let mut args = vec![];
+
+        while let Term::App(l, r) = term {
+            args.push(&**r);
+            term = l;
+        }
+
+        args.reverse();
+
+        // Redexes have no head of their own, so the abstraction stands in for the spine
+        let head = if let Term::Lam(..) = term {
+            core(ctxt, term, parent, f);
+            None
+        } else {
+            let head = Head::of(ctxt, term);
+            f(parent, head);
+            Some(head)
+        };
+
+        for (i, arg) in args.into_iter().enumerate() {
+            core(ctxt, arg, head.map(|head| (head, i)), f);
+        }
+    }
+
+    core(ctxt, term, None, f)
+}
+
+pub fn fit_unigram<'a>(
+    ctxt: &Context,
+    corpus: impl IntoIterator<Item = &'a Term>,
+    smoothing: f64,
+) -> Unigram {
+    // Otherwise unseen heads would have infinite cost
+    assert!(smoothing > 0., "Smoothing must be positive");
+
+    let heads = heads(ctxt);
+    let mut counts: HashMap<Head, f64> = heads.iter().map(|&h| (h, smoothing)).collect();
+
+    for term in corpus {
+        visit_heads(ctxt, term, &mut |_, head| {
+            *counts.entry(head).or_default() += 1.
+        });
+    }
+
+    let total: f64 = counts.values().sum();
+
+    Unigram {
+        log_probs: counts
+            .into_iter()
+            .map(|(head, count)| (head, (count / total).ln()))
+            .collect(),
+    }
+}
+
+// Bigram probabilities are smoothed towards the unigram ones, so unseen parents fall back
+// on the unigram distribution.
+pub fn fit_bigram<'a>(
+    ctxt: &Context,
+    corpus: impl IntoIterator<Item = &'a Term> + Clone,
+    smoothing: f64,
+) -> Bigram {
+    let unigram = fit_unigram(ctxt, corpus.clone(), smoothing);
+
+    let mut counts = HashMap::default();
+    let mut totals = HashMap::default();
+
+    for term in corpus {
+        visit_heads(ctxt, term, &mut |parent, head| {
+            *counts.entry((parent, head)).or_default() += 1.;
+            *totals.entry(parent).or_default() += 1.;
+        });
+    }
+
+    Bigram {
+        unigram,
+        counts,
+        totals,
+        smoothing,
+    }
+}
+
+impl Unigram {
+    pub fn log_prob(&self, head: Head) -> f64 {
+        self.log_probs
+            .get(&head)
+            .copied()
+            .unwrap_or(f64::NEG_INFINITY)
+    }
+
+    // Heads from most to least probable
+    pub fn weights(&self) -> Vec<(Head, f64)> {
+        let mut weights = self
+            .log_probs
+            .iter()
+            .map(|(&head, &log_prob)| (head, log_prob))
+            .collect::<Vec<_>>();
+
+        weights.sort_by(|(h1, p1), (h2, p2)| p2.total_cmp(p1).then(h1.cmp(h2)));
+        weights
+    }
+
+    // Values keep their usual cost of one unit.
+    pub fn prior(&self, unit: f64) -> Prior {
+        self.log_probs.iter().fold(
+            Prior::new(unit).with_fallback_log_prob(self.log_prob(Head::Bound)),
+            |prior, (head, &log_prob)| match head {
+                Head::Builtin(v) => prior.with_log_prob(*v, log_prob),
+                _ => prior,
+            },
+        )
+    }
+}
+
+impl Bigram {
+    pub fn unigram(&self) -> &Unigram {
+        &self.unigram
+    }
+
+    pub fn log_prob_given(&self, parent: Parent, head: Head) -> f64 {
+        let count = self.counts.get(&(parent, head)).copied().unwrap_or(0.);
+        let total = self.totals.get(&parent).copied().unwrap_or(0.);
+
+        let backoff = self.unigram.log_prob(head).exp();
+
+        ((count + self.smoothing * backoff) / (total + self.smoothing)).ln()
+    }
+
+    // Log-probability of the heads of `term`, ignoring its shape
+    pub fn log_prob(&self, ctxt: &Context, term: &Term) -> f64 {
+        let mut log_prob = 0.;
+        visit_heads(ctxt, term, &mut |parent, head| {
+            log_prob += self.log_prob_given(parent, head)
+        });
+        log_prob
+    }
+
+    // The most probable children of each parent, as `(parent, [(child, log_prob)])`
+    pub fn top_children(&self, n: usize) -> Vec<(Parent, Vec<(Head, f64)>)> {
+        let mut parents = self.totals.keys().copied().collect::<Vec<_>>();
+        parents.sort();
+
+        parents
+            .into_iter()
+            .map(|parent| {
+                let mut children = self
+                    .unigram
+                    .log_probs
+                    .keys()
+                    .map(|&head| (head, self.log_prob_given(parent, head)))
+                    .collect::<Vec<_>>();
+
+                children.sort_by(|(h1, p1), (h2, p2)| p2.total_cmp(p1).then(h1.cmp(h2)));
+                children.truncate(n);
+
+                (parent, children)
+            })
+            .collect()
+    }
+}
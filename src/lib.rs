@@ -1,10 +1,12 @@
 pub mod generate;
 pub mod lambda;
+pub mod learn;
 pub mod search;
 pub mod types;
 
 pub use generate::*;
 pub use lambda::*;
+pub use learn::*;
 pub use search::*;
 pub use types::*;
 
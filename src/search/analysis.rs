@@ -12,17 +12,23 @@ where
     Canonical(L::Semantics), // Group into equivalence class by canonical form
 }
 
-pub trait Language: Sized + Clone + Debug {
+// Languages (& their contexts) may be shared between search & synthesis threads.
+pub trait Language: Sized + Clone + Debug + Send + Sync {
     type Semantics: Semantics + Sized;
 
-    // Max size of `small` terms. (TODO: Make language-dependent)
+    // Max size of `small` terms, by default (see `MutationKernel`)
     const SMALL_SIZE: usize = 5;
 
-    // Max size of `large` terms. (TODO: Make language-dependent)
+    // Max size of `large` terms, by default (see `MutationKernel`)
     const LARGE_SIZE: usize = 10;
 
     fn context(&self) -> Context;
 
+    // Enumeration proceeds in order of increasing cost under this prior.
+    fn prior(&self) -> Prior {
+        Prior::default()
+    }
+
     fn sval(&self, _: &Value, _ty: &Type) -> Analysis<Self> {
         Analysis::Unique
     }
@@ -50,6 +56,30 @@ pub trait Language: Sized + Clone + Debug {
     }
 }
 
+// Analysis of an existing term, built up as the search would have, given the type of each
+// subterm.
+pub fn analyze_with<L: Language>(
+    lang: &L,
+    term: &Term,
+    ty_of: &dyn Fn(&Term) -> Type,
+) -> Analysis<L> {
+    use Term::*;
+    let ty = ty_of(term);
+
+    match term {
+        Val(v) => lang.sval(v, &ty),
+        Var(v) => lang.svar(*v, &ty),
+        Lam(v, b) => match analyze_with(lang, b, ty_of) {
+            Analysis::Malformed => Analysis::Malformed,
+            body => lang.slam(*v, body, &ty),
+        },
+        App(l, r) => match (analyze_with(lang, l, ty_of), analyze_with(lang, r, ty_of)) {
+            (Analysis::Malformed, _) | (_, Analysis::Malformed) => Analysis::Malformed,
+            (l, r) => lang.sapp(l, r, &ty),
+        },
+    }
+}
+
 impl<L: Language> Analysis<L> {
     pub fn canon(self) -> L::Semantics {
         use Analysis::*;
@@ -62,6 +92,16 @@ impl<L: Language> Analysis<L> {
     pub fn malformed(&self) -> bool {
         matches!(self, Self::Malformed)
     }
+
+    // Reinterprets the analysis for another language with the same semantics
+    pub fn cast<M: Language<Semantics = L::Semantics>>(self) -> Analysis<M> {
+        use Analysis::*;
+        match self {
+            Malformed => Malformed,
+            Unique => Unique,
+            Canonical(sem) => Canonical(sem),
+        }
+    }
 }
 
 impl<L> Display for Analysis<L>
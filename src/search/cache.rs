@@ -1,10 +1,12 @@
 use super::*;
 
-use rustc_hash::FxHashMap as HashMap;
+use rustc_hash::{FxHashMap as HashMap, FxHasher};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, Write};
 
-type Search = (Rc<Type>, usize);
+type Search = (Arc<Type>, usize);
 type PathDict = HashMap<Search, SearchResult>;
-type SemanticDict<L> = HashMap<(<L as Language>::Semantics, Type), (Term, usize)>;
+type SemanticDict<L> = HashMap<(Fingerprint<<L as Language>::Semantics>, Type), (Term, usize)>;
 
 #[derive(Debug, Default, Clone)]
 pub enum SearchResult {
@@ -18,6 +20,15 @@ pub struct Cache<L: Language> {
     paths: Vec<PathDict>,
     // Minimal sizes of representations of constants
     consts: Vec<SemanticDict<L>>,
+    // If set, closed terms without a canonical form are grouped by their outputs
+    probes: Option<Probes>,
+}
+
+// What is known about the scope of a variable
+#[derive(Clone, Debug)]
+pub struct CacheLayer<L: Language> {
+    paths: PathDict,
+    consts: SemanticDict<L>,
 }
 
 use SearchResult::*;
@@ -26,6 +37,16 @@ impl<L: Language> Cache<L> {
         Self {
             paths: vec![Default::default()],
             consts: vec![Default::default()],
+            probes: None,
+        }
+    }
+
+    // Deduplicates terms which agree on all of `probes`, in addition to
+    // those with the same canonical semantics.
+    pub fn observing(probes: Probes) -> Self {
+        Self {
+            probes: Some(probes),
+            ..Self::new()
         }
     }
 
@@ -45,22 +66,49 @@ impl<L: Language> Cache<L> {
         self.consts.push(Default::default());
     }
 
+    // Adds what `other` has established about the outermost scope.
+    // Semantic representatives are not shared, as they depend on enumeration order.
+    pub fn merge_paths(&mut self, other: &Self) {
+        for (search, result) in &other.paths[0] {
+            let entry = self.paths[0].entry(search.clone()).or_default();
+
+            match result {
+                Inhabited => *entry = Inhabited,
+                Empty if entry.unknown() => *entry = Empty,
+                _ => (),
+            }
+        }
+    }
+
     pub fn elim_var(&mut self) {
         self.paths.pop();
         self.consts.pop();
     }
 
-    pub fn prune(&self, targ: &Rc<Type>, size: usize) -> &SearchResult {
+    // Like `elim_var`, but the scope may later be restored with `resume_var`
+    pub fn suspend_var(&mut self) -> CacheLayer<L> {
+        CacheLayer {
+            paths: self.paths.pop().unwrap(),
+            consts: self.consts.pop().unwrap(),
+        }
+    }
+
+    pub fn resume_var(&mut self, layer: CacheLayer<L>) {
+        self.paths.push(layer.paths);
+        self.consts.push(layer.consts);
+    }
+
+    pub fn prune(&self, targ: &Arc<Type>, size: usize) -> &SearchResult {
         let search = (targ.clone(), size);
 
         self.active().get(&search).unwrap_or(&Unknown)
     }
 
-    pub fn prune_arg(&self, targ: &Rc<Type>, l_ty: &Rc<Type>, size: usize) -> SearchResult {
+    pub fn prune_arg(&self, targ: &Arc<Type>, l_ty: &Arc<Type>, size: usize) -> SearchResult {
         fn core<L: Language>(
             dict: &PathDict,
-            targ: &Rc<Type>,
-            l_ty: &Rc<Type>,
+            targ: &Arc<Type>,
+            l_ty: &Arc<Type>,
             size: usize,
         ) -> SearchResult {
             let done = l_ty == targ;
@@ -106,7 +154,7 @@ impl<L: Language> Cache<L> {
     }
 
     // Returns index of search for logging
-    pub fn begin_search(&mut self, targ: &Rc<Type>, size: usize) -> usize {
+    pub fn begin_search(&mut self, targ: &Arc<Type>, size: usize) -> usize {
         let search = (targ.clone(), size);
 
         self.active_mut().entry(search).or_insert(Unknown);
@@ -116,22 +164,27 @@ impl<L: Language> Cache<L> {
 
     pub fn yield_term(
         &mut self,
-        targ: &Rc<Type>,
+        ctxt: &Context,
+        targ: &Arc<Type>,
         size: usize,
         term: Term,
         analysis: Analysis<L>,
         depth: usize,
     ) -> Option<Term> {
-        use Analysis::*;
-        match &analysis {
-            Malformed => return None,
-            Unique => (),
-            Canonical(canon) => {
-                let entry = self
-                    .consts
-                    .last_mut()
-                    .unwrap()
-                    .entry((canon.clone(), (**targ).clone()));
+        if analysis.malformed() {
+            return None;
+        }
+
+        match self.fingerprint(ctxt, targ, &term, &analysis) {
+            None => (),
+            Some(fingerprint) => {
+                // Observed terms are closed, so they mean the same thing in every scope
+                let consts = match fingerprint {
+                    Fingerprint::Canonical(_) => self.consts.last_mut().unwrap(),
+                    Fingerprint::Observed(_) => &mut self.consts[0],
+                };
+
+                let entry = consts.entry((fingerprint, (**targ).clone()));
 
                 use std::collections::hash_map::Entry::*;
                 match entry {
@@ -184,6 +237,138 @@ impl<L: Language> Cache<L> {
         }
     }
 
+    fn fingerprint(
+        &self,
+        ctxt: &Context,
+        targ: &Type,
+        term: &Term,
+        analysis: &Analysis<L>,
+    ) -> Option<Fingerprint<L::Semantics>> {
+        use Analysis::*;
+        match analysis {
+            Malformed => None,
+            Canonical(canon) => Some(Fingerprint::Canonical(canon.clone())),
+            Unique => self
+                .probes
+                .as_ref()
+                .filter(|_| is_closed(term, ctxt))
+                .and_then(|probes| probes.observe(ctxt, term, targ))
+                .map(Fingerprint::Observed),
+        }
+    }
+
+    // Writes what is known about the outermost scope of a search over `vars`, with
+    // representatives of constants as terms (their fingerprints are recomputed on loading).
+    pub fn save(&self, lang: &L, vars: &VarsVec, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "fingerprint\t{:016x}", context_fingerprint(lang))?;
+        writeln!(out, "probes\t{:016x}", self.probes_fingerprint())?;
+
+        for (v, ty) in vars {
+            writeln!(out, "var\t{}\t{}", v, ty)?;
+        }
+
+        for ((ty, size), result) in &self.paths[0] {
+            let result = match result {
+                Inhabited => "inhabited",
+                Empty => "empty",
+                Unknown => continue,
+            };
+
+            writeln!(out, "path\t{}\t{}\t{}", size, result, ty)?;
+        }
+
+        for ((_, ty), (term, size)) in &self.consts[0] {
+            writeln!(out, "const\t{}\t{}\t{}", size, ty, term)?;
+        }
+
+        Ok(())
+    }
+
+    // Adds a saved cache, which must be for the same language, variables & probes
+    pub fn load(&mut self, lang: &L, vars: &VarsVec, input: impl BufRead) -> io::Result<()> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut fingerprint = None;
+        let mut probes = None;
+        let mut saved_vars = VarsVec::new();
+        let mut paths = vec![];
+        let mut consts = vec![];
+
+        for line in input.lines() {
+            let line = line?;
+            let fields = line.split('\t').collect::<Vec<_>>();
+
+            let ty = |s: &str| parse_type(s).map_err(|err| invalid(format!("{}: {}", line, err)));
+            let size = |s: &str| s.parse::<usize>().map_err(|_| invalid(line.clone()));
+
+            match fields[..] {
+                ["fingerprint", hex] => fingerprint = u64::from_str_radix(hex, 16).ok(),
+                ["probes", hex] => probes = u64::from_str_radix(hex, 16).ok(),
+                ["var", v, t] => saved_vars.push((Identifier::intern(v), Arc::new(ty(t)?))),
+                ["path", n, result, t] => {
+                    let result = match result {
+                        "inhabited" => Inhabited,
+                        "empty" => Empty,
+                        _ => return Err(invalid(line.clone())),
+                    };
+
+                    paths.push(((Arc::new(ty(t)?), size(n)?), result));
+                }
+                ["const", n, t, term] => {
+                    let term = parse_term(term).map_err(|err| invalid(err.to_string()))?;
+                    consts.push((term, ty(t)?, size(n)?));
+                }
+                _ => return Err(invalid(line.clone())),
+            }
+        }
+
+        if fingerprint != Some(context_fingerprint(lang)) {
+            return Err(invalid("cache was built for another language".into()));
+        }
+
+        // Which constants are equivalent depends on the probes
+        if probes != Some(self.probes_fingerprint()) {
+            return Err(invalid("cache was built with other probes".into()));
+        }
+
+        if saved_vars != *vars {
+            return Err(invalid("cache was built for other variables".into()));
+        }
+
+        for (search, result) in paths {
+            let entry = self.paths[0].entry(search).or_default();
+
+            if !entry.inhabited() {
+                *entry = result;
+            }
+        }
+
+        let ctxt = lang.context();
+
+        for (term, ty, size) in consts {
+            let analysis = reanalyze(lang, &ctxt, vars, &term, &ty)
+                .ok_or_else(|| invalid(format!("can't analyze `{}`", term)))?;
+
+            let Some(fingerprint) = self.fingerprint(&ctxt, &ty, &term, &analysis) else {
+                continue;
+            };
+
+            let entry = self.consts[0]
+                .entry((fingerprint, ty))
+                .or_insert((term.clone(), size));
+
+            if size < entry.1 {
+                *entry = (term, size);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn probes_fingerprint(&self) -> u64 {
+        self.probes.as_ref().map_or(0, Probes::fingerprint)
+    }
+
     pub fn active(&self) -> &PathDict {
         self.paths.last().unwrap()
     }
@@ -193,6 +378,12 @@ impl<L: Language> Cache<L> {
     }
 }
 
+impl<L: Language> Default for Cache<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SearchResult {
     //Add to space
     pub fn log(&mut self) {
@@ -211,3 +402,68 @@ impl SearchResult {
         matches!(self, Inhabited)
     }
 }
+
+// Identifies a language's builtins & prior, which determine what the search finds.
+// (Changes to its analysis can't be detected.)
+pub fn context_fingerprint<L: Language>(lang: &L) -> u64 {
+    let ctxt = lang.context();
+    let prior = lang.prior();
+
+    let mut builtins = ctxt
+        .iter()
+        .map(|(v, builtin)| {
+            let cost = prior.var_cost(*v);
+            // Weights within a cost depend on the exact log-probability
+            let log_prob = prior.var_log_prob(*v).to_bits();
+            let (ty, n_args) = (&builtin.ty, builtin.n_args);
+            format!("{} : {} / {} @ {} ({:x})", v, ty, n_args, cost, log_prob)
+        })
+        .collect::<Vec<_>>();
+
+    builtins.sort();
+
+    let mut hasher = FxHasher::default();
+
+    std::any::type_name::<L>().hash(&mut hasher);
+    builtins.hash(&mut hasher);
+    prior.unit().to_bits().hash(&mut hasher);
+    // Bound variables fall back on these
+    let fallback = Identifier::Uuid(u128::MAX);
+    prior.var_cost(fallback).hash(&mut hasher);
+    prior.var_log_prob(fallback).to_bits().hash(&mut hasher);
+
+    hasher.finish()
+}
+
+// The analysis the search would give `term`, in the scope of `vars`
+fn reanalyze<L: Language>(
+    lang: &L,
+    ctxt: &Context,
+    vars: &VarsVec,
+    term: &Term,
+    ty: &Type,
+) -> Option<Analysis<L>> {
+    // Abstracting over the variables makes the term closed
+    let closed = vars
+        .iter()
+        .rev()
+        .fold(term.clone(), |body, (v, _)| Term::Lam(*v, body.into()));
+
+    let closed_ty = vars.iter().rev().fold(ty.clone(), |ret, (_, arg)| {
+        Type::Fun(arg.clone(), ret.into())
+    });
+
+    let typing = type_subterms(ctxt, &closed, &closed_ty).ok()?;
+
+    let mut body = &closed;
+    for _ in vars {
+        let Term::Lam(_, b) = body else {
+            unreachable!()
+        };
+        body = b;
+    }
+
+    Some(analyze_with(lang, body, &|t| {
+        typing[&(t as *const Term)].0.clone()
+    }))
+}
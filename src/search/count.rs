@@ -0,0 +1,240 @@
+// Counting the terms `search` would enumerate, by dynamic programming over
+// (variables in scope, type, size) rather than by enumerating them.
+// Semantic pruning is ignored, so counts are exact for languages whose analysis never
+// rejects or merges terms, & otherwise an upper bound (the count before pruning).
+
+use super::*;
+
+pub use num_bigint::BigUint;
+use num_bigint::RandBigInt;
+use rand::Rng;
+use rustc_hash::FxHashMap as HashMap;
+
+// What is added up over terms: how many there are, or their total `Prior::weight`
+pub trait Tally: Clone {
+    fn zero() -> Self;
+    fn one() -> Self;
+    // Of a term consisting of the variable alone
+    fn var(prior: &Prior, ident: Identifier) -> Self;
+    fn is_zero(&self) -> bool;
+    fn add(&mut self, other: &Self);
+    fn mul(&self, other: &Self) -> Self;
+    // An index drawn in proportion to the tallies, which aren't all zero
+    fn choose(rng: &mut impl Rng, tallies: &[Self]) -> usize;
+}
+
+impl Tally for BigUint {
+    fn zero() -> Self {
+        BigUint::ZERO
+    }
+
+    fn one() -> Self {
+        BigUint::from(1u8)
+    }
+
+    fn var(_: &Prior, _: Identifier) -> Self {
+        Self::one()
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == BigUint::ZERO
+    }
+
+    fn add(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn choose(rng: &mut impl Rng, tallies: &[Self]) -> usize {
+        let total = tallies.iter().sum::<BigUint>();
+        let mut point = rng.gen_biguint_below(&total);
+
+        for (i, tally) in tallies.iter().enumerate() {
+            if point < *tally {
+                return i;
+            }
+
+            point -= tally;
+        }
+
+        unreachable!()
+    }
+}
+
+// Weights are products of those of the variables (other nodes weigh 1)
+impl Tally for f64 {
+    fn zero() -> Self {
+        0.
+    }
+
+    fn one() -> Self {
+        1.
+    }
+
+    fn var(prior: &Prior, ident: Identifier) -> Self {
+        (prior.var_log_prob(ident) + prior.unit() * prior.var_cost(ident) as f64).exp()
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0.
+    }
+
+    fn add(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn choose(rng: &mut impl Rng, tallies: &[Self]) -> usize {
+        let mut point = rng.gen::<f64>() * tallies.iter().sum::<f64>();
+
+        for (i, tally) in tallies.iter().enumerate() {
+            if point < *tally {
+                return i;
+            }
+
+            point -= tally;
+        }
+
+        // Rounding error
+        tallies.iter().rposition(|tally| *tally > 0.).unwrap()
+    }
+}
+
+// Remembers the counts of subproblems, so it should only be used with a single language.
+// Variables in scope are keyed sorted, like in `SizeCache`.
+#[derive(Clone, Debug, Default)]
+pub struct Counter<N: Tally = BigUint> {
+    all: HashMap<(VarsVec, Arc<Type>, usize), N>,
+    // Ways of applying a head of type `l_ty` to arguments, to get the target type
+    args: HashMap<(VarsVec, Arc<Type>, Arc<Type>, usize), N>,
+}
+
+pub fn count_terms<L: Language>(lang: &L, vars: VarsVec, targ: &Type, size: usize) -> BigUint {
+    Counter::new().count(lang, vars, targ, size)
+}
+
+impl<N: Tally> Counter<N> {
+    pub fn new() -> Self {
+        Self {
+            all: HashMap::default(),
+            args: HashMap::default(),
+        }
+    }
+
+    pub fn count<L: Language>(&mut self, lang: &L, vars: VarsVec, targ: &Type, size: usize) -> N {
+        let mut search_ctxt = SearchContext::new(lang, vars, Cache::new());
+
+        self.all(&mut search_ctxt, &Arc::new(targ.clone()), size)
+    }
+
+    fn key(search_ctxt: &SearchContext<impl Language>) -> VarsVec {
+        let mut args = search_ctxt.args.clone();
+        args.sort();
+        args
+    }
+
+    // Mirrors `Node::All`
+    pub(super) fn all<L: Language>(
+        &mut self,
+        search_ctxt: &mut SearchContext<L>,
+        targ: &Arc<Type>,
+        size: usize,
+    ) -> N {
+        if size == 0 {
+            return N::zero();
+        }
+
+        let key = (Self::key(search_ctxt), targ.clone(), size);
+
+        if let Some(count) = self.all.get(&key) {
+            return count.clone();
+        }
+
+        let mut count = N::zero();
+
+        for (var, v_ty) in search_ctxt.vars_producing(targ) {
+            let cost = search_ctxt.prior.var_cost(var);
+
+            if size < cost || (size == cost && v_ty != *targ) {
+                continue;
+            }
+
+            let head = N::var(&search_ctxt.prior, var);
+
+            if size == cost {
+                count.add(&head);
+            } else {
+                count.add(&head.mul(&self.args(search_ctxt, targ, &v_ty, size - cost)));
+            }
+        }
+
+        if let Type::Fun(arg, ret) = &**targ {
+            let ident = search_ctxt.vgen.small_var();
+
+            search_ctxt.vgen.retire(ident);
+            search_ctxt.args.push((ident, arg.clone()));
+
+            count.add(&self.all(search_ctxt, ret, size - 1));
+
+            search_ctxt.args.pop().unwrap();
+            search_ctxt.vgen.freshen(ident);
+        }
+
+        self.all.insert(key, count.clone());
+        count
+    }
+
+    // Mirrors `Node::Arg`, where `size` is what remains after the head
+    pub(super) fn args<L: Language>(
+        &mut self,
+        search_ctxt: &mut SearchContext<L>,
+        targ: &Arc<Type>,
+        l_ty: &Arc<Type>,
+        size: usize,
+    ) -> N {
+        if size == 1 {
+            return N::zero();
+        }
+
+        if size == 0 && targ == l_ty {
+            return N::one();
+        } else if size == 0 || targ == l_ty {
+            return N::zero();
+        }
+
+        let key = (Self::key(search_ctxt), targ.clone(), l_ty.clone(), size);
+
+        if let Some(count) = self.args.get(&key) {
+            return count.clone();
+        }
+
+        let Type::Fun(arg_ty, ret_ty) = &**l_ty else {
+            unreachable!()
+        };
+
+        // As in the enumerator, the argument takes up the rest once the type is reached
+        let smallest = if ret_ty == targ { size - 1 } else { 1 };
+
+        let mut count = N::zero();
+
+        for arg_size in smallest..size {
+            let args = self.all(search_ctxt, arg_ty, arg_size);
+
+            if args.is_zero() {
+                continue;
+            }
+
+            let rest = self.args(search_ctxt, targ, ret_ty, size - arg_size - 1);
+            count.add(&args.mul(&rest));
+        }
+
+        self.args.insert(key, count.clone());
+        count
+    }
+}
@@ -5,17 +5,27 @@
 
 mod analysis;
 mod cache;
+mod count;
 mod node;
+mod observe;
+mod parallel;
+mod prior;
+mod sample;
 mod semantics;
 
 pub use analysis::*;
+pub use count::*;
+pub use observe::*;
+pub use parallel::*;
+pub use prior::*;
+pub use sample::*;
 pub use semantics::*;
 
 use super::*;
 pub use cache::*;
 use node::*;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub fn search<'a, L: Language>(
     lang: &'a L,
@@ -33,24 +43,10 @@ pub fn search_with_cache<'a, L: Language>(
     size: usize,
     cache: Cache<L>,
 ) -> Enumerator<'a, L> {
-    let ctxt = lang.context();
-
-    let mut vgen = ctxt.vgen();
-
-    for (var, _) in &vars {
-        vgen.retire(*var);
-    }
-
     Enumerator {
-        search_ctxt: SearchContext {
-            lang,
-            ctxt,
-            vgen,
-            args: vars,
-            cache,
-        },
+        search_ctxt: SearchContext::new(lang, vars, cache),
         root: Node::All {
-            targ: Rc::new(targ.clone()),
+            targ: Arc::new(targ.clone()),
             size,
             state: None,
             phase: AllPhase::START,
@@ -70,7 +66,7 @@ impl<L:Language> Enumerator<'_, L> {
     }
 }
 
-pub type VarDecl = (Identifier, Rc<Type>);
+pub type VarDecl = (Identifier, Arc<Type>);
 pub type VarsVec = Vec<VarDecl>;
 
 struct SearchContext<'a, L: Language> {
@@ -80,43 +76,61 @@ struct SearchContext<'a, L: Language> {
     // Variables from abstractions
     args: VarsVec,
     cache: Cache<L>,
+    // Types over which polymorphic builtins are instantiated
+    ground: Vec<Type>,
+    prior: Prior,
 }
 
-impl<L: Language> SearchContext<'_, L> {
-    fn contains_var_of_type(&self, ty: &Rc<Type>) -> bool {
+impl<'a, L: Language> SearchContext<'a, L> {
+    fn new(lang: &'a L, vars: VarsVec, cache: Cache<L>) -> Self {
+        let ctxt = lang.context();
+
+        let mut vgen = ctxt.vgen();
+        let ground = ctxt.ground_types();
+
+        for (var, _) in &vars {
+            vgen.retire(*var);
+        }
+
+        Self {
+            lang,
+            ctxt,
+            vgen,
+            args: vars,
+            cache,
+            ground,
+            prior: lang.prior(),
+        }
+    }
+
+    fn contains_var_of_type(&self, ty: &Arc<Type>) -> bool {
         let args = self.args.iter().map(|(_, t)| t);
         let ctxt = self.ctxt.iter().map(|(_, b)| &b.ty);
 
         args.chain(ctxt).any(|v_ty| v_ty == ty)
     }
 
-    fn vars_producing(&mut self, targ: &Rc<Type>) -> VarsVec {
-        fn produces(ty: &Type, target: &Type) -> bool {
-            let ret_ty_produces = match ty {
-                Type::Fun(_, r) => produces(r, target),
-                _ => false,
-            };
+    fn vars_producing(&mut self, targ: &Arc<Type>) -> VarsVec {
+        let args = self.args.iter().map(|(_, ty)| &**ty).chain([&**targ]);
 
-            ret_ty_produces || target == ty
-        }
+        let mut ground = self.ground.clone();
+        ground.extend(args.flat_map(|ty| ground_types(ty, &[])));
+        ground.sort();
+        ground.dedup();
 
-        let var_produces = move |(v, ty): (Identifier, &Rc<Type>)| {
-            if produces(ty, targ) {
-                Some((v, ty.clone()))
-            } else {
-                None
-            }
-        };
+        let builtins = self.ctxt.iter().flat_map(|(&v, builtin)| {
+            instances_producing(builtin, targ, &ground)
+                .into_iter()
+                .map(move |inst| (v, Arc::new(inst)))
+        });
 
-        let vars = self
-            .ctxt
+        let args = self
+            .args
             .iter()
-            .map(|(&v, BuiltIn { ty, .. })| (v, ty))
-            .chain(self.args.iter().map(|(v, t)| (*v, t)))
-            .filter_map(var_produces)
-            .collect();
+            .filter(|(_, ty)| produces(ty, targ))
+            .cloned();
 
-        vars
+        builtins.chain(args).collect()
     }
 }
 
@@ -126,6 +140,5 @@ impl<L: Language> Iterator for Enumerator<'_, L> {
     fn next(&mut self) -> Option<Self::Item> {
         self.root
             .next(&mut self.search_ctxt)
-            .map(|(t, a)| (t.deep_clone(), a))
     }
 }
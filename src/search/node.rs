@@ -1,35 +1,37 @@
 use super::*;
 
-use std::rc::Rc;
+use std::sync::Arc;
 use SearchResult::*;
 
 #[derive(Clone, Debug)]
 pub(super) enum Node<L: Language> {
     All {
-        targ: Rc<Type>,
+        targ: Arc<Type>,
         size: usize,
         phase: AllPhase,
         state: Option<Box<Node<L>>>,
         depth: Option<usize>,
     },
     Abs {
-        targ: Rc<Type>,
+        targ: Arc<Type>,
         size: usize,
         ident: Option<Identifier>,
         state: Option<Box<Node<L>>>,
+        // Scope of the abstraction, while it is not being searched
+        scope: Option<CacheLayer<L>>,
     },
     Var {
-        targ: Rc<Type>,
+        targ: Arc<Type>,
         size: usize,
         vars: VarsVec,
         state: Option<Box<Node<L>>>,
         depth: usize,
     },
     Arg {
-        targ: Rc<Type>,
+        targ: Arc<Type>,
         size: usize,
-        l_ty: Rc<Type>,
-        left: Thunk,
+        l_ty: Arc<Type>,
+        left: Arc<Term>,
         left_analysis: Analysis<L>,
         res: SearchResult,
         state: Option<Box<Node<L>>>,
@@ -45,6 +47,9 @@ pub(super) enum AllPhase {
     Application,
     Abstraction,
     Completed,
+    // Only part of the search was performed (see `Node::branch`), so
+    // we cannot conclude anything once it finishes.
+    Partial,
 }
 
 impl AllPhase {
@@ -52,6 +57,45 @@ impl AllPhase {
 }
 
 impl<L: Language> Node<L> {
+    // Root of a search which only explores a single branch of the `All` node.
+    pub fn branch(
+        targ: Arc<Type>,
+        size: usize,
+        branch: Branch,
+        search_ctxt: &mut SearchContext<L>,
+    ) -> Self {
+        if size == 0 || search_ctxt.cache.prune(&targ, size).empty() {
+            return Node::Nil;
+        }
+
+        let depth = search_ctxt.cache.begin_search(&targ, size);
+
+        let state = match branch {
+            Branch::Head(var) => Node::Var {
+                targ: targ.clone(),
+                size,
+                vars: vec![var],
+                state: None,
+                depth,
+            },
+            Branch::Abstraction => Node::Abs {
+                targ: targ.clone(),
+                size,
+                ident: None,
+                state: None,
+                scope: None,
+            },
+        };
+
+        Node::All {
+            targ,
+            size,
+            phase: AllPhase::Partial,
+            state: Some(Box::new(state)),
+            depth: Some(depth),
+        }
+    }
+
     pub fn next(&mut self, search_ctxt: &mut SearchContext<L>) -> Option<(Term, Analysis<L>)> {
         use Node::*;
         loop {
@@ -79,6 +123,7 @@ impl<L: Language> Node<L> {
                         match curr_state.next(search_ctxt) {
                             Some((term, analysis)) => {
                                 if let Some(term) = search_ctxt.cache.yield_term(
+                                    &search_ctxt.ctxt,
                                     targ,
                                     size,
                                     term,
@@ -124,6 +169,7 @@ impl<L: Language> Node<L> {
                                 ident: None,
                                 size,
                                 state: None,
+                                scope: None,
                             }))
                         }
                         Completed => {
@@ -131,6 +177,7 @@ impl<L: Language> Node<L> {
                             search_ctxt.cache.end_search(search);
                             return None;
                         }
+                        Partial => return None,
                     };
                 }
 
@@ -139,6 +186,7 @@ impl<L: Language> Node<L> {
                     size,
                     ident,
                     state,
+                    scope,
                 } => {
                     let Type::Fun(arg, ret) = &**targ else {
                         *self = Nil;
@@ -147,33 +195,48 @@ impl<L: Language> Node<L> {
 
                     let ident = *ident.get_or_insert_with(|| search_ctxt.vgen.small_var());
 
-                    if let Some(curr_state) = state {
-                        return match curr_state.next(search_ctxt) {
-                            Some((term, analysis)) => {
-                                let term = Term::Lam(ident, term.into());
+                    // The variable is only in scope while the body is being searched,
+                    // so that it cannot leak into sibling arguments.
+                    search_ctxt.vgen.retire(ident);
+                    search_ctxt.args.push((ident, arg.clone()));
 
-                                let analysis = search_ctxt.lang.slam(ident, analysis, targ);
-                                Some((term, analysis))
-                            }
-                            None => {
-                                self.exit(search_ctxt);
-                                None
-                            }
-                        };
-                    };
+                    match scope.take() {
+                        Some(layer) => search_ctxt.cache.resume_var(layer),
+                        None => {
+                            let is_new = !search_ctxt.contains_var_of_type(arg);
+                            search_ctxt.cache.intro_var(is_new);
+                        }
+                    }
 
-                    search_ctxt.args.push((ident, arg.clone()));
+                    let body = state.get_or_insert_with(|| {
+                        Box::new(All {
+                            targ: ret.clone(),
+                            size: *size - 1,
+                            state: None,
+                            phase: AllPhase::START,
+                            depth: None,
+                        })
+                    });
 
-                    let is_new = !search_ctxt.contains_var_of_type(arg);
-                    search_ctxt.cache.intro_var(is_new);
+                    let next = body.next(search_ctxt);
 
-                    *state = Some(Box::new(All {
-                        targ: ret.clone(),
-                        size: *size - 1,
-                        state: None,
-                        phase: AllPhase::START,
-                        depth: None,
-                    }));
+                    search_ctxt.args.pop().unwrap();
+                    search_ctxt.vgen.freshen(ident);
+                    let layer = search_ctxt.cache.suspend_var();
+
+                    return match next {
+                        Some((term, analysis)) => {
+                            *scope = Some(layer);
+
+                            let term = Term::Lam(ident, term.into());
+                            let analysis = search_ctxt.lang.slam(ident, analysis, targ);
+                            Some((term, analysis))
+                        }
+                        None => {
+                            *self = Nil;
+                            None
+                        }
+                    };
                 }
 
                 Var {
@@ -194,8 +257,13 @@ impl<L: Language> Node<L> {
 
                     let size = *size;
                     let depth = *depth;
+                    let cost = search_ctxt.prior.var_cost(var);
 
-                    if size == 1 {
+                    if size < cost {
+                        continue;
+                    }
+
+                    if size == cost {
                         if v_ty == *targ {
                             return Some((Term::Var(var), search_ctxt.lang.svar(var, targ)));
                         } else {
@@ -207,7 +275,7 @@ impl<L: Language> Node<L> {
 
                     *state = Some(Box::new(Arg {
                         targ: targ.clone(),
-                        size: size - 1,
+                        size: size - cost,
                         l_ty: v_ty,
                         left: Term::Var(var).into(),
                         left_analysis: analysis,
@@ -258,7 +326,7 @@ impl<L: Language> Node<L> {
                             unreachable!()
                         };
 
-                        return Some((left.borrow().clone(), left_analysis.clone()));
+                        return Some(((*left).clone(), left_analysis.clone()));
                     } else if size == 0 || targ == l_ty {
                         *self = Nil;
                         return None;
@@ -272,7 +340,6 @@ impl<L: Language> Node<L> {
                         *res = search_ctxt.cache.prune_arg(targ, l_ty, size);
 
                         if res.empty() {
-                            self.exit(search_ctxt);
                             *self = Nil;
                             return None;
                         }
@@ -314,8 +381,7 @@ impl<L: Language> Node<L> {
 
                     let ((arg, arg_analysis), arg_size) = loop {
                         if let Some(arg) = arg_state.next(search_ctxt) {
-                            let size = arg.0.size();
-                            break (arg, size);
+                            break (arg, arg_state_size);
                         }
 
                         if arg_state_size == size - 1 {
@@ -340,8 +406,9 @@ impl<L: Language> Node<L> {
                     let left = Term::App(left.clone(), arg.into());
 
                     if let Some(term) = search_ctxt.cache.yield_term(
+                        &search_ctxt.ctxt,
                         ret_ty,
-                        left.size(),
+                        search_ctxt.prior.cost(&left),
                         left,
                         analysis.clone(),
                         depth,
@@ -363,44 +430,6 @@ impl<L: Language> Node<L> {
             }
         }
     }
-
-    pub fn exit(&mut self, search_ctxt: &mut SearchContext<L>) {
-        use Node::*;
-        match self {
-            All { state, .. } => {
-                if let Some(state) = state.take().as_mut() {
-                    state.exit(search_ctxt);
-                }
-            }
-            Abs {
-                state: Some(state),
-                ident,
-                ..
-            } => {
-                state.exit(search_ctxt);
-                let ident = ident.unwrap();
-                search_ctxt.args.pop().unwrap();
-                search_ctxt.vgen.freshen(ident);
-                search_ctxt.cache.elim_var();
-            }
-            Var {
-                state: Some(state), ..
-            } => {
-                state.exit(search_ctxt);
-            }
-            Arg {
-                state, arg_state, ..
-            } => {
-                if let Some(state) = state.take().as_mut() {
-                    state.exit(search_ctxt);
-                }
-                if let Some(state) = arg_state.take().as_mut() {
-                    state.exit(search_ctxt);
-                }
-            }
-            _ => {}
-        }
-    }
 }
 
 use std::fmt::*;
@@ -441,6 +470,7 @@ impl<L: Language + Debug> Display for Node<L> {
                 size,
                 ident,
                 state,
+                ..
             } => {
                 write!(
                     f,
@@ -497,7 +527,7 @@ impl<L: Language + Debug> Display for Node<L> {
                     targ,
                     size,
                     res,
-                    left.borrow(),
+                    left,
                     left_analysis,
                     l_ty,
                     indent = indent
@@ -0,0 +1,133 @@
+// Observational equivalence.
+// Closed terms are evaluated on a fixed set of probe inputs, & terms whose outputs
+// agree on every probe are treated as equivalent (see `Cache::observing`).
+// This is only an approximation of semantic equality, so it must be opted into.
+
+use super::*;
+
+use rustc_hash::{FxHashMap as HashMap, FxHasher};
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Debug, Default)]
+pub struct Probes {
+    pub inputs: HashMap<Type, Vec<Term>>,
+    // Applied to each probe separately
+    pub budget: Budget,
+}
+
+// Outputs of a term on every combination of probes, in a fixed order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation(Vec<Term>);
+
+// Key of the semantic dictionary in `Cache`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Fingerprint<S: Semantics> {
+    Canonical(S),
+    Observed(Observation),
+}
+
+impl Probes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, ty: Type, inputs: impl IntoIterator<Item = Term>) -> Self {
+        self.inputs.entry(ty).or_default().extend(inputs);
+        self
+    }
+
+    // Identifies the probes & budget, which determine which terms are equivalent
+    pub fn fingerprint(&self) -> u64 {
+        let mut inputs = self
+            .inputs
+            .iter()
+            .map(|(ty, terms)| {
+                let terms = terms.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+                format!("{} : {}", ty, terms.join(", "))
+            })
+            .collect::<Vec<_>>();
+
+        inputs.sort();
+
+        let mut hasher = FxHasher::default();
+
+        inputs.hash(&mut hasher);
+        let Budget {
+            steps,
+            builtin_calls,
+            depth,
+        } = self.budget;
+        (steps, builtin_calls, depth).hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    // Fails if some argument type has no probes, or if any evaluation fails.
+    pub fn observe(&self, ctxt: &Context, term: &Term, ty: &Type) -> Option<Observation> {
+        let mut args = vec![];
+        let mut ty = ty;
+
+        while let Type::Fun(arg, ret) = ty {
+            let inputs = self.inputs.get(arg).filter(|inputs| !inputs.is_empty())?;
+            args.push(inputs);
+            ty = ret;
+        }
+
+        let mut outputs = vec![];
+        let mut indices = vec![0; args.len()];
+
+        loop {
+            let app = args
+                .iter()
+                .zip(&indices)
+                .fold(term.clone(), |app, (inputs, &i)| {
+                    Term::App(app.into(), inputs[i].clone().into())
+                });
+
+            outputs.push(ctxt.evaluate_with(&app, self.budget).ok()?);
+
+            // Advance to the next combination of probes
+            let Some(pos) = (0..args.len())
+                .rev()
+                .find(|&pos| indices[pos] + 1 < args[pos].len())
+            else {
+                return Some(Observation(outputs));
+            };
+
+            indices[pos] += 1;
+            indices[pos + 1..].fill(0);
+        }
+    }
+}
+
+// Free variables of closed terms may only refer to builtins.
+pub fn is_closed(term: &Term, ctxt: &Context) -> bool {
+    fn core(term: &Term, ctxt: &Context, bound: &mut Vec<Identifier>) -> bool {
+        use Term::*;
+        match term {
+            Val(_) => true,
+            Var(v) => bound.contains(v) || ctxt.get(*v).is_some(),
+            Lam(v, b) => {
+                bound.push(*v);
+                let closed = core(b, ctxt, bound);
+                bound.pop();
+                closed
+            }
+            App(l, r) => core(l, ctxt, bound) && core(r, ctxt, bound),
+        }
+    }
+
+    core(term, ctxt, &mut vec![])
+}
+
+// Values only need `PartialEq`, so we hash the printed outputs instead.
+// Equal outputs print the same, so this is consistent with `Eq`.
+impl Hash for Observation {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for output in &self.0 {
+            output.to_string().hash(state);
+        }
+    }
+}
+
+impl Eq for Observation {}
@@ -0,0 +1,152 @@
+// Parallel enumeration.
+// The root `All` node is split into one job per head variable (& one for abstractions),
+// which are handed out to worker threads. Each worker has its own `Cache`, but
+// inhabitation knowledge is pooled between jobs. Terms are streamed back through a
+// bounded channel per job, so workers only run a little ahead of the reader.
+
+use super::*;
+
+use rustc_hash::FxHashSet as HashSet;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Mutex;
+
+#[derive(Clone, Debug)]
+pub(super) enum Branch {
+    Head(VarDecl),
+    Abstraction,
+}
+
+pub fn par_search<L: Language + 'static>(
+    lang: &L,
+    vars: VarsVec,
+    targ: &Type,
+    size: usize,
+) -> ParSearch<L> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    par_search_with(lang, vars, targ, size, threads)
+}
+
+// Terms found by the workers, yielded as they come in
+pub struct ParSearch<L: Language> {
+    // One per branch, in order
+    found: VecDeque<Receiver<(Term, Analysis<L>)>>,
+    seen: HashSet<L::Semantics>,
+    // Set when dropped, so workers don't start on jobs nobody will read
+    stop: Arc<AtomicBool>,
+}
+
+impl<L: Language> ParSearch<L> {
+    // Terms each branch may find ahead of the one being read
+    const BUFFER: usize = 1024;
+}
+
+// Yields the same terms as `search`, in the same order. If the language has canonical
+// semantics, each class is still produced once, but the representative may differ,
+// since workers do not see the representatives found by other workers.
+pub fn par_search_with<L: Language + 'static>(
+    lang: &L,
+    vars: VarsVec,
+    targ: &Type,
+    size: usize,
+    threads: usize,
+) -> ParSearch<L> {
+    let targ = Arc::new(targ.clone());
+
+    // `Node::Var` pops variables from the back, so we reverse to match `search`'s order
+    let mut branches = SearchContext::new(lang, vars.clone(), Cache::new())
+        .vars_producing(&targ)
+        .into_iter()
+        .rev()
+        .map(Branch::Head)
+        .collect::<Vec<_>>();
+    branches.push(Branch::Abstraction);
+
+    let (senders, found): (Vec<_>, VecDeque<_>) = branches
+        .iter()
+        .map(|_| {
+            let (sender, receiver) = sync_channel(ParSearch::<L>::BUFFER);
+            (Mutex::new(Some(sender)), receiver)
+        })
+        .unzip();
+
+    let shared = Arc::new((branches, senders));
+    let next_job = Arc::new(AtomicUsize::new(0));
+    let knowledge = Arc::new(Mutex::new(Cache::<L>::new()));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    for _ in 0..threads.clamp(1, shared.0.len()) {
+        let (lang, vars, targ) = (lang.clone(), vars.clone(), targ.clone());
+        let (shared, next_job) = (shared.clone(), next_job.clone());
+        let (knowledge, stop) = (knowledge.clone(), stop.clone());
+
+        std::thread::spawn(move || loop {
+            let (branches, senders) = &*shared;
+            let job = next_job.fetch_add(1, Ordering::Relaxed);
+
+            let Some(branch) = branches.get(job) else {
+                return;
+            };
+
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let sender = senders[job].lock().unwrap().take().unwrap();
+
+            let mut cache = Cache::new();
+            cache.merge_paths(&knowledge.lock().unwrap());
+
+            let mut search_ctxt = SearchContext::new(&lang, vars.clone(), cache);
+            let root = Node::branch(targ.clone(), size, branch.clone(), &mut search_ctxt);
+
+            let mut enumerator = Enumerator { search_ctxt, root };
+
+            for found in enumerator.by_ref() {
+                // Nobody is reading any more
+                if sender.send(found).is_err() {
+                    return;
+                }
+            }
+
+            knowledge.lock().unwrap().merge_paths(&enumerator.cache());
+        });
+    }
+
+    ParSearch {
+        found,
+        seen: HashSet::default(),
+        stop,
+    }
+}
+
+impl<L: Language> Iterator for ParSearch<L> {
+    type Item = (Term, Analysis<L>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Ok((term, analysis)) = self.found.front()?.recv() else {
+                // That branch is done
+                self.found.pop_front();
+                continue;
+            };
+
+            // Branches may produce the same class, so we deduplicate again at the root.
+            if let Analysis::Canonical(sem) = &analysis {
+                if !self.seen.insert(sem.clone()) {
+                    continue;
+                }
+            }
+
+            return Some((term, analysis));
+        }
+    }
+}
+
+impl<L: Language> Drop for ParSearch<L> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
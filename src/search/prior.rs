@@ -0,0 +1,140 @@
+// Description length of terms under a prior over identifiers.
+// Log-probabilities are quantized into integer costs, so that the enumerator can search
+// bands of (roughly) equal description length, just as it would search sizes.
+// Under the uniform prior, the cost of a term is its size.
+
+use super::*;
+
+use rustc_hash::FxHashMap as HashMap;
+
+#[derive(Clone, Debug)]
+pub struct Prior {
+    log_probs: HashMap<Identifier, f64>,
+    // Log-probability of identifiers without their own (e.g. bound variables)
+    fallback: Option<f64>,
+    // Nats per unit of cost. Otherwise unweighted identifiers cost one unit,
+    // as do abstractions, applications & values.
+    unit: f64,
+}
+
+impl Prior {
+    pub fn new(unit: f64) -> Self {
+        assert!(unit > 0., "Cost unit must be positive");
+
+        Self {
+            log_probs: HashMap::default(),
+            fallback: None,
+            unit,
+        }
+    }
+
+    pub fn with_log_prob(mut self, ident: impl Into<Identifier>, log_prob: f64) -> Self {
+        self.log_probs.insert(ident.into(), log_prob);
+        self
+    }
+
+    pub fn with_prob(self, ident: impl Into<Identifier>, prob: f64) -> Self {
+        self.with_log_prob(ident, prob.ln())
+    }
+
+    pub fn with_fallback_log_prob(mut self, log_prob: f64) -> Self {
+        self.fallback = Some(log_prob);
+        self
+    }
+
+    pub fn unit(&self) -> f64 {
+        self.unit
+    }
+
+    fn lookup(&self, ident: Identifier) -> Option<f64> {
+        self.log_probs.get(&ident).copied().or(self.fallback)
+    }
+
+    pub fn var_log_prob(&self, ident: Identifier) -> f64 {
+        self.lookup(ident).unwrap_or(-self.unit)
+    }
+
+    // Every identifier costs at least one unit, so that enumeration terminates.
+    pub fn var_cost(&self, ident: Identifier) -> usize {
+        match self.lookup(ident) {
+            Some(log_prob) => (-log_prob / self.unit).round().max(1.) as usize,
+            None => 1,
+        }
+    }
+
+    pub fn cost(&self, term: &Term) -> usize {
+        use Term::*;
+        match term {
+            Val(_) => 1,
+            Var(v) => self.var_cost(*v),
+            Lam(_, b) => 1 + self.cost(b),
+            App(l, r) => 1 + self.cost(l) + self.cost(r),
+        }
+    }
+
+    pub fn log_prob(&self, term: &Term) -> f64 {
+        use Term::*;
+        match term {
+            Val(_) => -self.unit,
+            Var(v) => self.var_log_prob(*v),
+            Lam(_, b) => -self.unit + self.log_prob(b),
+            App(l, r) => -self.unit + self.log_prob(l) + self.log_prob(r),
+        }
+    }
+
+    // Probability of `term`, relative to others of the same cost.
+    // (This stays close to 1, where the probability itself may underflow.)
+    pub fn weight(&self, term: &Term) -> f64 {
+        (self.log_prob(term) + self.unit * self.cost(term) as f64).exp()
+    }
+}
+
+impl Default for Prior {
+    fn default() -> Self {
+        Self::new(1.)
+    }
+}
+
+// Replaces the prior of an existing language.
+#[derive(Clone, Debug)]
+pub struct Weighted<L: Language> {
+    pub lang: L,
+    pub prior: Prior,
+}
+
+impl<L: Language> Weighted<L> {
+    pub fn new(lang: L, prior: Prior) -> Self {
+        Self { lang, prior }
+    }
+}
+
+impl<L: Language> Language for Weighted<L> {
+    type Semantics = L::Semantics;
+
+    const SMALL_SIZE: usize = L::SMALL_SIZE;
+    const LARGE_SIZE: usize = L::LARGE_SIZE;
+
+    fn context(&self) -> Context {
+        self.lang.context()
+    }
+
+    fn prior(&self) -> Prior {
+        self.prior.clone()
+    }
+
+    fn sval(&self, val: &Value, ty: &Type) -> Analysis<Self> {
+        self.lang.sval(val, ty).cast()
+    }
+
+    fn svar(&self, ident: Identifier, ty: &Type) -> Analysis<Self> {
+        self.lang.svar(ident, ty).cast()
+    }
+
+    fn slam(&self, ident: Identifier, body: Analysis<Self>, ty: &Type) -> Analysis<Self> {
+        self.lang.slam(ident, body.cast(), ty).cast()
+    }
+
+    fn sapp(&self, fun: Analysis<Self>, arg: Analysis<Self>, ty: &Type) -> Analysis<Self> {
+        self.lang.sapp(fun.cast(), arg.cast(), ty).cast()
+    }
+}
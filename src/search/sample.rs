@@ -0,0 +1,164 @@
+// Random terms of a given type & size, drawn by walking down the choices of the enumerator
+// in proportion to the number (or weight) of terms each leads to, as tallied by a `Counter`.
+// Nothing is enumerated, so this scales to sizes far beyond `search`. As with `Counter`,
+// semantic pruning is ignored: terms the enumerator would reject (or merge) may be drawn,
+// so callers should check the analysis if that matters. (Bound variables may also be
+// named differently than by the enumerator.)
+
+use super::*;
+
+use rand::Rng;
+
+// Uniform over all terms (before pruning). Ends immediately if there are none.
+pub fn sample_terms<'a, L: Language, R: Rng>(
+    lang: &'a L,
+    vars: VarsVec,
+    targ: &Type,
+    size: usize,
+    rng: &'a mut R,
+) -> impl Iterator<Item = (Term, Analysis<L>)> + 'a {
+    let mut counter = Counter::<BigUint>::new();
+    let targ = targ.clone();
+
+    std::iter::from_fn(move || counter.sample(lang, vars.clone(), &targ, size, rng))
+}
+
+impl<N: Tally> Counter<N> {
+    // Uniformly with counts, or under the prior with weights (see `Tally`)
+    pub fn sample<L: Language>(
+        &mut self,
+        lang: &L,
+        vars: VarsVec,
+        targ: &Type,
+        size: usize,
+        rng: &mut impl Rng,
+    ) -> Option<(Term, Analysis<L>)> {
+        let mut search_ctxt = SearchContext::new(lang, vars, Cache::new());
+        let targ = Arc::new(targ.clone());
+
+        if self.all(&mut search_ctxt, &targ, size).is_zero() {
+            return None;
+        }
+
+        Some(self.sample_all(&mut search_ctxt, &targ, size, rng))
+    }
+
+    fn sample_all<L: Language>(
+        &mut self,
+        search_ctxt: &mut SearchContext<L>,
+        targ: &Arc<Type>,
+        size: usize,
+        rng: &mut impl Rng,
+    ) -> (Term, Analysis<L>) {
+        let mut heads = vec![];
+        let mut tallies = vec![];
+
+        for (var, v_ty) in search_ctxt.vars_producing(targ) {
+            let cost = search_ctxt.prior.var_cost(var);
+
+            if size < cost || (size == cost && v_ty != *targ) {
+                continue;
+            }
+
+            let head = N::var(&search_ctxt.prior, var);
+
+            if size == cost {
+                tallies.push(head);
+            } else {
+                tallies.push(head.mul(&self.args(search_ctxt, targ, &v_ty, size - cost)));
+            }
+
+            heads.push((var, v_ty));
+        }
+
+        // The abstraction comes last
+        if let Type::Fun(arg, ret) = &**targ {
+            let ident = search_ctxt.vgen.small_var();
+
+            search_ctxt.vgen.retire(ident);
+            search_ctxt.args.push((ident, arg.clone()));
+
+            let body = self.all(search_ctxt, ret, size - 1);
+
+            if N::choose(rng, &[tallies.iter().fold(N::zero(), sum), body]) == 1 {
+                let (body, analysis) = self.sample_all(search_ctxt, ret, size - 1, rng);
+
+                search_ctxt.args.pop().unwrap();
+                search_ctxt.vgen.freshen(ident);
+
+                let analysis = search_ctxt.lang.slam(ident, analysis, targ);
+                return (Term::Lam(ident, body.into()), analysis);
+            }
+
+            search_ctxt.args.pop().unwrap();
+            search_ctxt.vgen.freshen(ident);
+        }
+
+        let (var, v_ty) = heads.swap_remove(N::choose(rng, &tallies));
+        let cost = search_ctxt.prior.var_cost(var);
+
+        if size == cost {
+            return (Term::Var(var), search_ctxt.lang.svar(var, targ));
+        }
+
+        let analysis = search_ctxt.lang.svar(var, &v_ty);
+
+        self.sample_args(
+            search_ctxt,
+            targ,
+            &v_ty,
+            size - cost,
+            (Term::Var(var), analysis),
+            rng,
+        )
+    }
+
+    fn sample_args<L: Language>(
+        &mut self,
+        search_ctxt: &mut SearchContext<L>,
+        targ: &Arc<Type>,
+        l_ty: &Arc<Type>,
+        size: usize,
+        (left, left_analysis): (Term, Analysis<L>),
+        rng: &mut impl Rng,
+    ) -> (Term, Analysis<L>) {
+        if size == 0 {
+            return (left, left_analysis);
+        }
+
+        let Type::Fun(arg_ty, ret_ty) = &**l_ty else {
+            unreachable!()
+        };
+
+        let smallest = if ret_ty == targ { size - 1 } else { 1 };
+
+        let tallies = (smallest..size)
+            .map(|arg_size| {
+                let args = self.all(search_ctxt, arg_ty, arg_size);
+                let rest = self.args(search_ctxt, targ, ret_ty, size - arg_size - 1);
+                args.mul(&rest)
+            })
+            .collect::<Vec<_>>();
+
+        let arg_size = smallest + N::choose(rng, &tallies);
+
+        let (arg, arg_analysis) = self.sample_all(search_ctxt, arg_ty, arg_size, rng);
+
+        let analysis = search_ctxt.lang.sapp(left_analysis, arg_analysis, targ);
+        let left = Term::App(left.into(), arg.into());
+
+        self.sample_args(
+            search_ctxt,
+            targ,
+            ret_ty,
+            size - arg_size - 1,
+            (left, analysis),
+            rng,
+        )
+    }
+}
+
+fn sum<N: Tally>(mut total: N, tally: &N) -> N {
+    total.add(tally);
+    total
+}
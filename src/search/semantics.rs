@@ -4,11 +4,11 @@ use std::{
 };
 
 pub trait Semantics:
-    Debug + Clone + PartialEq + Eq + Hash + Display + Sized
+    Debug + Clone + PartialEq + Eq + Hash + Display + Sized + Send + Sync
 {
 }
 
-impl<T: Debug + Clone + Eq + Hash + Display + Sized> Semantics for T {}
+impl<T: Debug + Clone + Eq + Hash + Display + Sized + Send + Sync> Semantics for T {}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct OpaqueSemantics;
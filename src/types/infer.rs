@@ -0,0 +1,233 @@
+// Hindley-Milner style inference.
+// Builtins may be polymorphic (see `BuiltIn::forall`), but lambda-bound variables are not,
+// since there is no `let` to generalize over.
+
+use super::*;
+
+use rustc_hash::FxHashMap as HashMap;
+use std::fmt::{self, Display};
+use std::result::Result;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeError {
+    Unbound(Identifier),
+    Mismatch { expected: Arc<Type>, found: Arc<Type> },
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TypeError::*;
+        match self {
+            Unbound(v) => write!(f, "unbound variable `{}`", v),
+            Mismatch { expected, found } => {
+                write!(f, "expected type `{}`, found `{}`", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+// Type of each subterm & the variables in scope there, keyed by address.
+pub type Typing = HashMap<*const Term, (Type, VarsVec)>;
+
+// Type of a closed term. Undetermined parts of the type are left as `Uuid` variables.
+pub fn infer(ctxt: &Context, term: &Term) -> Result<Type, TypeError> {
+    let mut inference = Inference::new(ctxt);
+    let ty = inference.term(term, &mut vec![])?;
+
+    Ok(inference.resolve(ty))
+}
+
+// Checks `term` against `ty`, typing every subterm along the way.
+pub fn type_subterms(ctxt: &Context, term: &Term, ty: &Type) -> Result<Typing, TypeError> {
+    let mut inference = Inference::new(ctxt);
+    inference.notes = Some(vec![]);
+
+    let found = inference.term(term, &mut vec![])?;
+    inference.unify(ty, &found)?;
+
+    let notes = inference.notes.take().unwrap();
+
+    Ok(notes
+        .into_iter()
+        .map(|(ptr, ty, decls)| {
+            let decls = decls
+                .into_iter()
+                .map(|(v, ty)| (v, Arc::new(inference.resolve(ty))))
+                .collect();
+
+            (ptr, (inference.resolve(ty), decls))
+        })
+        .collect())
+}
+
+type Env = Vec<(Identifier, Type)>;
+
+struct Inference<'a> {
+    ctxt: &'a Context,
+    sub: TypeSub,
+    // If set, records the type of each subterm
+    notes: Option<Vec<(*const Term, Type, Env)>>,
+}
+
+impl<'a> Inference<'a> {
+    fn new(ctxt: &'a Context) -> Self {
+        Self {
+            ctxt,
+            sub: TypeSub::default(),
+            notes: None,
+        }
+    }
+
+    fn term(&mut self, term: &Term, env: &mut Env) -> Result<Type, TypeError> {
+        use Term::*;
+        let ty = match term {
+            // Values carry no type information
+            Val(_) => Type::Var(uuid()),
+            Var(v) => match env.iter().rev().find(|(s, _)| s == v) {
+                Some((_, ty)) => ty.clone(),
+                None => self
+                    .ctxt
+                    .get(*v)
+                    .ok_or(TypeError::Unbound(*v))?
+                    .instantiate(),
+            },
+            Lam(v, b) => {
+                let arg = Type::Var(uuid());
+
+                env.push((*v, arg.clone()));
+                let ret = self.term(b, env);
+                env.pop();
+
+                Type::Fun(arg.into(), ret?.into())
+            }
+            App(l, r) => {
+                let fun = self.term(l, env)?;
+                let arg = self.term(r, env)?;
+                let ret = Type::Var(uuid());
+
+                self.unify(&Type::Fun(arg.into(), ret.clone().into()), &fun)?;
+
+                ret
+            }
+        };
+
+        if let Some(notes) = &mut self.notes {
+            notes.push((term as *const Term, ty.clone(), env.clone()));
+        }
+
+        Ok(ty)
+    }
+
+    fn unify(&mut self, expected: &Type, found: &Type) -> Result<(), TypeError> {
+        match self.sub.unify(expected, found) {
+            Some(_) => Ok(()),
+            None => Err(TypeError::Mismatch {
+                expected: self.resolve(expected.clone()).into(),
+                found: self.resolve(found.clone()).into(),
+            }),
+        }
+    }
+
+    fn resolve(&self, mut ty: Type) -> Type {
+        self.sub.apply(&mut ty);
+        ty
+    }
+}
+
+// Monomorphic instances of `builtin` which produce `targ` after some number of arguments.
+// Variables not determined by `targ` range over `ground`.
+pub fn instances_producing(builtin: &BuiltIn, targ: &Type, ground: &[Type]) -> Vec<Type> {
+    if builtin.forall.is_empty() {
+        return if produces(&builtin.ty, targ) {
+            vec![(*builtin.ty).clone()]
+        } else {
+            vec![]
+        };
+    }
+
+    let ty = builtin.instantiate();
+    let mut instances = vec![];
+    let mut ret = &ty;
+
+    loop {
+        let mut sub = TypeSub::default();
+
+        if sub.unify(ret, targ).is_some() {
+            let mut inst = ty.clone();
+            sub.apply(&mut inst);
+
+            let mut free = inst
+                .vars()
+                .into_iter()
+                .filter(is_flexible)
+                .collect::<Vec<_>>();
+            free.sort();
+
+            for assignment in assignments(free.len(), ground.len()) {
+                let sub: TypeSub = free
+                    .iter()
+                    .zip(assignment)
+                    .map(|(v, i)| (*v, ground[i].clone()))
+                    .collect();
+
+                let mut ground_inst = inst.clone();
+                sub.apply(&mut ground_inst);
+
+                if !instances.contains(&ground_inst) {
+                    instances.push(ground_inst);
+                }
+            }
+        }
+
+        let Type::Fun(_, next) = ret else {
+            break;
+        };
+
+        ret = next;
+    }
+
+    instances
+}
+
+// Atomic types appearing in `ty` (excluding the quantified ones)
+pub fn ground_types(ty: &Type, forall: &[Identifier]) -> impl Iterator<Item = Type> {
+    let forall = forall.to_vec();
+
+    ty.vars()
+        .into_iter()
+        .filter(move |v| !is_flexible(v) && !forall.contains(v))
+        .map(Type::Var)
+}
+
+pub fn produces(ty: &Type, targ: &Type) -> bool {
+    let ret_ty_produces = match ty {
+        Type::Fun(_, r) => produces(r, targ),
+        _ => false,
+    };
+
+    ret_ty_produces || targ == ty
+}
+
+fn is_flexible(v: &Identifier) -> bool {
+    matches!(v, Identifier::Uuid(_))
+}
+
+// All ways of choosing `n` of `k` things (with repetition), in lexicographic order
+fn assignments(n: usize, k: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut next = (n == 0 || k > 0).then(|| vec![0; n]);
+
+    std::iter::from_fn(move || {
+        let curr = next.take()?;
+
+        if let Some(pos) = (0..n).rev().find(|&pos| curr[pos] + 1 < k) {
+            let mut succ = curr.clone();
+            succ[pos] += 1;
+            succ[pos + 1..].fill(0);
+            next = Some(succ);
+        }
+
+        Some(curr)
+    })
+}
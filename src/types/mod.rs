@@ -1,15 +1,20 @@
+pub mod infer;
 pub mod subs;
+pub use infer::*;
 pub use subs::*;
 
 use super::*;
 
 use rustc_hash::FxHashSet as HashSet;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Type {
     Var(Identifier),
-    Fun(Rc<Type>, Rc<Type>),
+    Fun(Arc<Type>, Arc<Type>),
+    // Applied type constructor (e.g. `List N`, `Pair A B`). Always has arguments,
+    // since nullary constructors are just (rigid) variables.
+    Con(Identifier, Vec<Arc<Type>>),
 }
 
 impl Type {
@@ -28,6 +33,7 @@ impl Type {
                 }
                 vars
             }
+            Con(_, args) => args.iter().flat_map(|arg| arg.vars()).collect(),
         }
     }
 }
@@ -48,6 +54,16 @@ impl Display for Type {
                 write!(f, "=>{}", r)?;
                 write!(f, ")")
             }
+            Con(c, args) => {
+                write!(f, "{}", c)?;
+                for arg in args {
+                    match &**arg {
+                        Con(_, _) => write!(f, " ({})", arg)?,
+                        _ => write!(f, " {}", arg)?,
+                    }
+                }
+                Ok(())
+            }
         }
     }
 }
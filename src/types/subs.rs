@@ -7,68 +7,66 @@ pub struct TypeSub {
 }
 
 impl TypeSub {
+    // Only `Uuid` variables may be substituted. Named variables (`N`, `Bool`, ...) are rigid.
     pub fn unify(&mut self, lhs: &Type, rhs: &Type) -> Option<Type> {
+        use Identifier::Uuid;
         use Type::*;
 
-        type RefSub<'a> = HashMap<Identifier, &'a Type>;
+        let mut news = HashMap::default();
+        let mut stack = vec![(lhs.clone(), rhs.clone())];
 
-        fn contains(sub: &RefSub, s: Identifier, t: &Type) -> bool {
-            match t {
-                Var(v) if *v == s => true,
-                Var(v) => {
-                    if let Some(t) = sub.get(v) {
-                        contains(sub, s, t)
-                    } else {
-                        false
-                    }
-                }
-                Fun(l, r) => contains(sub, s, l) || contains(sub, s, r),
-            }
-        }
+        while let Some((l, r)) = stack.pop() {
+            let l = self.resolve(&news, l);
+            let r = self.resolve(&news, r);
 
-        let mut subs = RefSub::default();
-        let mut stack = vec![(lhs, rhs)];
-
-        while let Some(pair) = stack.pop() {
-            match pair {
+            match (&l, &r) {
                 (Var(x), Var(y)) if x == y => continue,
-                (t, Var(v)) | (Var(v), t) => {
-                    if contains(&subs, *v, t) {
+                (t, Var(v @ Uuid(_))) | (Var(v @ Uuid(_)), t) => {
+                    if self.occurs(&news, *v, t) {
                         return None;
                     }
 
-                    if let Some(expected) = self.dict.get(v) {
-                        stack.push((t, expected));
-                    }
-
-                    let Some(expected) = subs.get(v) else {
-                        subs.insert(*v, t);
-                        continue;
-                    };
-
-                    stack.push((t, expected));
+                    news.insert(*v, t.clone());
                 }
                 (Fun(lx, ly), Fun(rx, ry)) => {
-                    stack.push((lx, rx));
-                    stack.push((ly, ry));
+                    stack.push(((**lx).clone(), (**rx).clone()));
+                    stack.push(((**ly).clone(), (**ry).clone()));
+                }
+                (Con(lc, largs), Con(rc, rargs)) if lc == rc && largs.len() == rargs.len() => {
+                    let args = largs.iter().zip(rargs);
+                    stack.extend(args.map(|(l, r)| ((**l).clone(), (**r).clone())));
                 }
+                _ => return None,
             }
         }
 
-        let news: Vec<_> = subs
-            .into_iter()
-            .map(|(id, mono)| (id, mono.clone()))
-            .collect();
-
-        for (id, mono) in news {
-            self.dict.insert(id, mono.clone());
-        }
+        self.dict.extend(news);
 
         let mut ty = lhs.clone();
         self.apply(&mut ty);
         Some(ty)
     }
 
+    // Follows substitutions at the head of `ty`
+    fn resolve(&self, news: &HashMap<Identifier, Type>, mut ty: Type) -> Type {
+        while let Type::Var(v) = ty {
+            match news.get(&v).or_else(|| self.dict.get(&v)) {
+                Some(next) => ty = next.clone(),
+                None => break,
+            }
+        }
+
+        ty
+    }
+
+    fn occurs(&self, news: &HashMap<Identifier, Type>, var: Identifier, ty: &Type) -> bool {
+        match self.resolve(news, ty.clone()) {
+            Type::Var(v) => v == var,
+            Type::Fun(l, r) => self.occurs(news, var, &l) || self.occurs(news, var, &r),
+            Type::Con(_, args) => args.iter().any(|arg| self.occurs(news, var, arg)),
+        }
+    }
+
     pub fn apply(&self, ty: &mut Type) {
         use Type::*;
         match ty {
@@ -79,9 +77,22 @@ impl TypeSub {
                 }
             }
             Fun(l, r) => {
-                self.apply(Rc::make_mut(l));
-                self.apply(Rc::make_mut(r));
+                self.apply(Arc::make_mut(l));
+                self.apply(Arc::make_mut(r));
             }
+            Con(_, args) => {
+                for arg in args {
+                    self.apply(Arc::make_mut(arg));
+                }
+            }
+        }
+    }
+}
+
+impl FromIterator<(Identifier, Type)> for TypeSub {
+    fn from_iter<T: IntoIterator<Item = (Identifier, Type)>>(iter: T) -> Self {
+        Self {
+            dict: HashMap::from_iter(iter),
         }
     }
 }